@@ -0,0 +1,101 @@
+//! Values that move smoothly towards a target over time, see [Ui::animate_value](crate::Ui::animate_value).
+
+use hashbrown::HashMap;
+
+/// Curve a value follows from its start to its target, `t` goes from `0.0` to `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing {
+    Linear,
+    /// Starts slow.
+    EaseIn,
+    /// Stops slow.
+    #[default]
+    EaseOut,
+    /// Starts and stops slow (smoothstep).
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Animation {
+    from: f32,
+    to: f32,
+    started: f32,
+    duration: f32,
+    easing: Easing,
+    /// Frame it was last used in, unused animations are dropped.
+    frame: u64,
+}
+
+impl Animation {
+    fn value(&self, time: f32) -> f32 {
+        let t = if self.duration > 0.0 {
+            (time - self.started) / self.duration
+        } else {
+            1.0
+        };
+
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+}
+
+/// Animations of all widgets, keyed by widget ID and what is animated.
+#[derive(Debug, Default)]
+pub(crate) struct Animations {
+    values: HashMap<(usize, &'static str), Animation>,
+}
+
+impl Animations {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current value of the animation `key`. A new target starts a new animation from the current value, the first
+    /// call jumps straight to the target.
+    pub(crate) fn animate(
+        &mut self,
+        key: (usize, &'static str),
+        target: f32,
+        duration: f32,
+        easing: Easing,
+        time: f32,
+        frame: u64,
+    ) -> f32 {
+        let animation = self.values.entry(key).or_insert(Animation {
+            from: target,
+            to: target,
+            started: time,
+            duration,
+            easing,
+            frame,
+        });
+
+        if animation.to != target {
+            animation.from = animation.value(time);
+            animation.to = target;
+            animation.started = time;
+            animation.duration = duration;
+            animation.easing = easing;
+        }
+
+        animation.frame = frame;
+        animation.value(time)
+    }
+
+    /// Drops the animations of widgets that were not drawn in `frame`.
+    pub(crate) fn end_frame(&mut self, frame: u64) {
+        self.values.retain(|_, animation| animation.frame == frame);
+    }
+}
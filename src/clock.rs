@@ -0,0 +1,35 @@
+use wut::sys;
+
+/// Frame clock, ticked once per [Guii::build](crate::Guii::build).
+#[derive(Debug, Default)]
+pub struct Clock {
+    last: Option<i64>,
+    /// Seconds since the first frame.
+    pub time: f32,
+    /// Seconds since the previous frame.
+    pub delta: f32,
+    pub frame: u64,
+}
+
+impl Clock {
+    /// Upper bound for [Clock::delta] so a stall (e.g. the HOME menu) doesn't skip animations or fire repeats.
+    const MAX_DELTA: f32 = 0.1;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn tick(&mut self) {
+        let now = unsafe { sys::OSGetSystemTime() };
+        let ticks_per_second = unsafe { (*sys::OSGetSystemInfo()).busClockSpeed / 4 } as f32;
+
+        self.delta = match self.last {
+            Some(last) => ((now - last) as f32 / ticks_per_second).min(Self::MAX_DELTA),
+            None => 0.0,
+        };
+
+        self.last = Some(now);
+        self.time += self.delta;
+        self.frame += 1;
+    }
+}
@@ -1,54 +1,513 @@
-use wut::gamepad::{Button, State};
+use crate::{
+    GuiiError,
+    input::{Buttons, ClassicButton, Input, Kpad, WiiRemoteButton},
+};
+use alloc::string::String;
+use core::fmt::Debug;
+use flagset::{FlagSet, Flags, flags};
+use wut::{format, gamepad::Button, vec::Vec};
+
+/// Button chords of one controller type bound to actions.
+///
+/// An action can have several bindings. A binding is a chord: it fires when all its buttons are held and at least
+/// one of them was pressed this frame. If a longer chord fires, the bindings it contains are dropped, so binding
+/// `L + Up` doesn't also trigger `L` and `Up`.
+#[derive(Debug, Clone)]
+pub struct Bindings<B: Flags> {
+    bindings: Vec<(Action, FlagSet<B>)>,
+}
+
+impl<B: Flags + Copy + Debug> Bindings<B> {
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Adds `chord` as another binding for `action`.
+    pub fn with(mut self, action: Action, chord: impl Into<FlagSet<B>>) -> Self {
+        self.bind(action, chord);
+        self
+    }
+
+    /// Adds `chord` as another binding for `action`.
+    pub fn bind(&mut self, action: Action, chord: impl Into<FlagSet<B>>) {
+        let chord = chord.into();
+
+        if !chord.is_empty() && !self.bindings.contains(&(action, chord)) {
+            self.bindings.push((action, chord));
+        }
+    }
+
+    /// Removes all bindings of `action`.
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.retain(|(a, _)| *a != action);
+    }
+
+    /// Chords bound to `action`.
+    pub fn chords(&self, action: Action) -> impl Iterator<Item = FlagSet<B>> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(a, _)| *a == action)
+            .map(|(_, chord)| *chord)
+    }
+
+    /// Held directions, used for auto-repeat.
+    pub fn held(&self, hold: FlagSet<B>) -> FlagSet<Action> {
+        self.holding(hold) & Action::directions()
+    }
+
+    /// All actions whose chord is held, e.g. to show help while [Action::Help] is held.
+    pub fn holding(&self, hold: FlagSet<B>) -> FlagSet<Action> {
+        self.fire(|chord| hold.contains(chord))
+    }
+
+    /// Actions whose chord was completed this frame.
+    pub fn check(&self, hold: FlagSet<B>, trigger: FlagSet<B>) -> FlagSet<Action> {
+        let down = hold | trigger;
+        self.fire(|chord| down.contains(chord) && !(trigger & chord).is_empty())
+    }
+
+    fn fire(&self, fires: impl Fn(FlagSet<B>) -> bool) -> FlagSet<Action> {
+        let fired: Vec<_> = self
+            .bindings
+            .iter()
+            .filter(|(_, chord)| fires(*chord))
+            .collect();
+
+        fired
+            .iter()
+            .filter(|(_, chord)| {
+                !fired
+                    .iter()
+                    .any(|(_, other)| other != chord && other.contains(*chord))
+            })
+            .fold(FlagSet::default(), |actions, (action, _)| actions | *action)
+    }
+
+    fn save(&self, controller: &str, out: &mut String) {
+        for action in Action::LIST {
+            let chords: Vec<_> = self.chords(*action).map(|chord| names(chord)).collect();
+
+            // Unbound actions are written with an empty value so loading them clears the defaults.
+            let line = format!("{}.{:?} = {}", controller, action, chords.join(", "));
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+
+    /// Parses `A + B, X` into chords. An empty value means no chords.
+    fn parse(value: &str) -> Result<Vec<FlagSet<B>>, String> {
+        if value.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        value
+            .split(',')
+            .map(|chord| {
+                chord
+                    .split('+')
+                    .map(|name| parse_flag::<B>(name.trim()))
+                    .try_fold(FlagSet::default(), |chord, button| Ok(chord | button?))
+            })
+            .collect()
+    }
+}
+
+impl<B: Flags + Copy + Debug> Default for Bindings<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn names<F: Flags + Debug>(set: FlagSet<F>) -> String {
+    set.into_iter()
+        .map(|flag| format!("{:?}", flag))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn parse_flag<F: Flags + Debug>(name: &str) -> Result<F, String> {
+    F::LIST
+        .iter()
+        .find(|flag| format!("{:?}", flag).eq_ignore_ascii_case(name))
+        .copied()
+        .ok_or_else(|| format!("unknown name `{}`", name))
+}
+
+/// Auto-repeat of held directions.
+#[derive(Debug, Clone, Copy)]
+pub struct Repeat {
+    /// Seconds a direction must be held before it repeats.
+    pub delay: f32,
+    /// Repeats per second once repeating starts.
+    pub rate: f32,
+    /// Added to `rate` for every second held after `delay`.
+    pub acceleration: f32,
+    pub max_rate: f32,
+    /// Added to the steps per repeat for every second held after `delay`, see [Repeat::steps].
+    pub step_acceleration: f32,
+}
+
+impl Repeat {
+    /// Repeats per second after holding for `held` seconds.
+    pub fn rate(&self, held: f32) -> f32 {
+        (self.rate + self.acceleration * (held - self.delay).max(0.0)).min(self.max_rate)
+    }
+
+    /// How many steps value widgets (e.g. [Number](crate::widgets::number::Number)) should take per repeat after holding for `held` seconds.
+    pub fn steps(&self, held: f32) -> u32 {
+        1 + (self.step_acceleration * (held - self.delay).max(0.0)) as u32
+    }
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Self {
+            delay: 0.4,
+            rate: 8.0,
+            acceleration: 8.0,
+            max_rate: 30.0,
+            step_acceleration: 2.0,
+        }
+    }
+}
+
+/// Left stick as D-pad and analog value input.
+#[derive(Debug, Clone, Copy)]
+pub struct Stick {
+    pub enabled: bool,
+    /// Deflection at which the stick counts as a direction press.
+    pub press: f32,
+    /// Deflection below which the direction is released again. Lower than `press` so one flick moves once.
+    pub release: f32,
+    /// Value steps per second at full horizontal deflection, e.g. for [Number](crate::widgets::number::Number).
+    pub analog_rate: f32,
+}
+
+impl Default for Stick {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            press: 0.6,
+            release: 0.3,
+            analog_rate: 20.0,
+        }
+    }
+}
+
+/// How directions move the focus between widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Navigation {
+    /// Up/Down go to the previous/next widget in the order they were added.
+    #[default]
+    Linear,
+    /// Directions go to the closest widget on screen in that direction, like TV UIs. Falls back to
+    /// [Navigation::Linear] for Up/Down if there is none.
+    Spatial,
+}
 
 #[derive(Debug)]
 pub struct Controls {
-    pub up: Button,
-    pub down: Button,
-    pub left: Button,
-    pub right: Button,
-    pub accept: Button,
-    pub cancel: Button,
+    pub gamepad: Bindings<Button>,
+    /// Wii Remote held sideways, so the D-pad is rotated.
+    pub wiimote: Bindings<WiiRemoteButton>,
+    pub classic: Bindings<ClassicButton>,
+    pub pro: Bindings<ClassicButton>,
+    pub repeat: Repeat,
+    pub stick: Stick,
+    /// Focus wraps from the last widget to the first and back.
+    pub wrap: bool,
+    pub navigation: Navigation,
 }
 
 impl Controls {
-    pub fn check(&self, input: &State) -> Action {
-        if input.trigger.contains(self.up) {
-            Action::Up
-        } else if input.trigger.contains(self.down) {
-            Action::Down
-        } else if input.trigger.contains(self.left) {
-            Action::Left
-        } else if input.trigger.contains(self.right) {
-            Action::Right
-        } else if input.trigger.contains(self.accept) {
-            Action::Accept
-        } else if input.trigger.contains(self.cancel) {
-            Action::Cancel
-        } else {
-            Action::None
+    /// Actions triggered on any connected controller.
+    pub fn check(&self, input: &Input) -> FlagSet<Action> {
+        self.resolve(input, false)
+    }
+
+    /// Directions held on any connected controller.
+    pub fn held(&self, input: &Input) -> FlagSet<Action> {
+        self.resolve(input, true) & Action::directions()
+    }
+
+    /// All actions held on any connected controller.
+    pub fn holding(&self, input: &Input) -> FlagSet<Action> {
+        self.resolve(input, true)
+    }
+
+    fn resolve(&self, input: &Input, hold: bool) -> FlagSet<Action> {
+        fn pick<B: Flags + Copy + Debug>(
+            bindings: &Bindings<B>,
+            buttons: &Buttons<B>,
+            hold: bool,
+        ) -> FlagSet<Action> {
+            if hold {
+                bindings.holding(buttons.hold)
+            } else {
+                bindings.check(buttons.hold, buttons.trigger)
+            }
+        }
+
+        let gamepad = input.gamepad.as_ref().map(|state| {
+            if hold {
+                self.gamepad.holding(state.hold)
+            } else {
+                self.gamepad.check(state.hold, state.trigger)
+            }
+        });
+
+        gamepad
+            .into_iter()
+            .chain(input.kpads.iter().map(|kpad| match kpad {
+                Kpad::WiiRemote(buttons) => pick(&self.wiimote, buttons, hold),
+                Kpad::Classic(buttons) => pick(&self.classic, buttons, hold),
+                Kpad::Pro(buttons) => pick(&self.pro, buttons, hold),
+            }))
+            .fold(FlagSet::default(), |actions, a| actions | a)
+    }
+
+    /// Writes all bindings as text, one `controller.Action = Chord, Chord` line per action, e.g.
+    ///
+    /// ```text
+    /// gamepad.Accept = A
+    /// gamepad.Home = L + Up
+    /// gamepad.Help =
+    /// ```
+    pub fn save_bindings(&self) -> String {
+        let mut out = String::new();
+
+        self.gamepad.save("gamepad", &mut out);
+        self.wiimote.save("wiimote", &mut out);
+        self.classic.save("classic", &mut out);
+        self.pro.save("pro", &mut out);
+
+        out
+    }
+
+    /// Loads bindings written by [Controls::save_bindings]. Every action listed replaces the bindings of that action,
+    /// an empty value (`gamepad.Help =`) unbinds it, all other bindings are kept. Empty lines and lines starting with `#` are ignored.
+    pub fn load_bindings(&mut self, text: &str) -> Result<(), GuiiError> {
+        for (i, line) in text.lines().enumerate() {
+            self.load_line(line.trim())
+                .map_err(|message| GuiiError::Parse {
+                    line: i + 1,
+                    message,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn load_line(&mut self, line: &str) -> Result<(), String> {
+        fn apply<B: Flags + Copy + Debug>(
+            bindings: &mut Bindings<B>,
+            action: Action,
+            value: &str,
+        ) -> Result<(), String> {
+            let chords = Bindings::<B>::parse(value)?;
+
+            bindings.unbind(action);
+            for chord in chords {
+                bindings.bind(action, chord);
+            }
+
+            Ok(())
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| String::from("expected `controller.Action = Chord`"))?;
+        let (controller, action) = key
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| format!("expected `controller.Action`, found `{}`", key.trim()))?;
+        let action = parse_flag::<Action>(action.trim())?;
+        let value = value.trim();
+
+        match controller.trim() {
+            "gamepad" => apply(&mut self.gamepad, action, value),
+            "wiimote" => apply(&mut self.wiimote, action, value),
+            "classic" => apply(&mut self.classic, action, value),
+            "pro" => apply(&mut self.pro, action, value),
+            controller => Err(format!("unknown controller `{}`", controller)),
         }
     }
 }
 
 impl Default for Controls {
     fn default() -> Self {
+        let classic = Bindings::new()
+            .with(Action::Up, ClassicButton::Up)
+            .with(Action::Down, ClassicButton::Down)
+            .with(Action::Left, ClassicButton::Left)
+            .with(Action::Right, ClassicButton::Right)
+            .with(Action::Accept, ClassicButton::A)
+            .with(Action::Cancel, ClassicButton::B)
+            .with(Action::PageUp, ClassicButton::L)
+            .with(Action::PageDown, ClassicButton::R)
+            .with(Action::TabPrev, ClassicButton::ZL)
+            .with(Action::TabNext, ClassicButton::ZR)
+            .with(Action::Home, ClassicButton::L | ClassicButton::Up)
+            .with(Action::End, ClassicButton::R | ClassicButton::Down)
+            .with(Action::Help, ClassicButton::X)
+            .with(Action::Context, ClassicButton::Y);
+
         Self {
-            up: Button::Up,
-            down: Button::Down,
-            left: Button::Left,
-            right: Button::Right,
-            accept: Button::A,
-            cancel: Button::B,
+            gamepad: Bindings::new()
+                .with(Action::Up, Button::Up)
+                .with(Action::Down, Button::Down)
+                .with(Action::Left, Button::Left)
+                .with(Action::Right, Button::Right)
+                .with(Action::Accept, Button::A)
+                .with(Action::Cancel, Button::B)
+                .with(Action::PageUp, Button::L)
+                .with(Action::PageDown, Button::R)
+                .with(Action::TabPrev, Button::ZL)
+                .with(Action::TabNext, Button::ZR)
+                .with(Action::Home, Button::L | Button::Up)
+                .with(Action::End, Button::R | Button::Down)
+                .with(Action::Help, Button::X)
+                .with(Action::Context, Button::Y),
+            wiimote: Bindings::new()
+                .with(Action::Up, WiiRemoteButton::Right)
+                .with(Action::Down, WiiRemoteButton::Left)
+                .with(Action::Left, WiiRemoteButton::Up)
+                .with(Action::Right, WiiRemoteButton::Down)
+                .with(Action::Accept, WiiRemoteButton::Two)
+                .with(Action::Cancel, WiiRemoteButton::One)
+                .with(Action::PageUp, WiiRemoteButton::Minus)
+                .with(Action::PageDown, WiiRemoteButton::Plus)
+                .with(Action::TabPrev, WiiRemoteButton::B | WiiRemoteButton::Minus)
+                .with(Action::TabNext, WiiRemoteButton::B | WiiRemoteButton::Plus)
+                .with(Action::Help, WiiRemoteButton::A),
+            pro: classic.clone(),
+            classic,
+            repeat: Repeat::default(),
+            stick: Stick::default(),
+            wrap: false,
+            navigation: Navigation::default(),
         }
     }
 }
 
-pub enum Action {
-    None,
-    Up,
-    Down,
-    Left,
-    Right,
-    Accept,
-    Cancel,
+flags! {
+    /// Input actions. Several can happen in the same frame, so they are passed around as `FlagSet<Action>`.
+    #[derive(Hash)]
+    pub enum Action: u16 {
+        Up,
+        Down,
+        Left,
+        Right,
+        Accept,
+        Cancel,
+        PageUp,
+        PageDown,
+        TabNext,
+        TabPrev,
+        /// Jump to the first item.
+        Home,
+        /// Jump to the last item.
+        End,
+        Help,
+        /// Context menu.
+        Context,
+    }
+}
+
+impl Action {
+    pub fn directions() -> FlagSet<Action> {
+        Action::Up | Action::Down | Action::Left | Action::Right
+    }
+
+    /// Highest priority direction in `actions`.
+    pub fn direction(actions: FlagSet<Action>) -> Option<Action> {
+        (actions & Self::directions()).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Pro = Bindings<ClassicButton>;
+
+    #[test]
+    fn parse_chords() {
+        assert_eq!(
+            Pro::parse("A + B, X").unwrap(),
+            [ClassicButton::A | ClassicButton::B, ClassicButton::X.into()]
+        );
+        assert_eq!(
+            Pro::parse(" zl+R ").unwrap(),
+            [ClassicButton::ZL | ClassicButton::R]
+        );
+        assert!(Pro::parse("").unwrap().is_empty());
+        assert_eq!(Pro::parse("A + Q").unwrap_err(), "unknown name `Q`");
+    }
+
+    #[test]
+    fn load_replaces_and_unbinds() {
+        let mut controls = Controls::default();
+        controls
+            .load_bindings("pro.accept = b + a\n# comment\n\npro.Help =")
+            .unwrap();
+
+        assert_eq!(
+            controls.pro.chords(Action::Accept).collect::<Vec<_>>(),
+            [ClassicButton::A | ClassicButton::B]
+        );
+        assert_eq!(controls.pro.chords(Action::Help).count(), 0);
+        // other controllers keep their bindings
+        assert_eq!(
+            controls.classic.chords(Action::Accept).collect::<Vec<_>>(),
+            Controls::default()
+                .classic
+                .chords(Action::Accept)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unbound_actions_survive_a_round_trip() {
+        let mut controls = Controls::default();
+        controls.pro.unbind(Action::Accept);
+
+        let mut loaded = Controls::default();
+        loaded.load_bindings(&controls.save_bindings()).unwrap();
+
+        assert_eq!(loaded.pro.chords(Action::Accept).count(), 0);
+        assert_eq!(loaded.save_bindings(), controls.save_bindings());
+    }
+
+    #[test]
+    fn load_errors_have_line_numbers() {
+        let error = Controls::default().load_bindings("pro.Accept = A\npro.Jump = B");
+
+        assert!(matches!(
+            error,
+            Err(GuiiError::Parse { line: 2, message }) if message == "unknown name `Jump`"
+        ));
+    }
+
+    #[test]
+    fn longer_chords_shadow_the_ones_they_contain() {
+        let bindings = Pro::new()
+            .with(Action::Home, ClassicButton::L | ClassicButton::Up)
+            .with(Action::Up, ClassicButton::Up)
+            .with(Action::PageUp, ClassicButton::L);
+        let (l, up) = (FlagSet::from(ClassicButton::L), ClassicButton::Up.into());
+
+        assert_eq!(bindings.check(l | up, up), Action::Home);
+        assert_eq!(bindings.check(up, up), Action::Up);
+        assert_eq!(bindings.check(l, l), Action::PageUp);
+        // a chord only fires when one of its buttons was pressed this frame
+        assert!(bindings.check(l | up, FlagSet::default()).is_empty());
+        assert_eq!(bindings.holding(l | up), Action::Home);
+    }
 }
@@ -4,10 +4,13 @@ use wut::gx2::types::Vec2;
 pub struct Layout {
     pub origin: Vec2<f32>,
     pub current: Vec2<f32>,
+    /// Unused, widgets are spaced by [Style::spacing](super::style::Style::spacing).
+    #[deprecated(note = "use `Style::spacing`")]
     pub gap: Vec2<f32>,
 }
 
 impl Layout {
+    #[allow(deprecated)]
     pub fn new(x: f32, y: f32) -> Self {
         Self {
             origin: Vec2::new(x, y),
@@ -1,12 +1,19 @@
 pub mod controls;
 pub mod layout;
 pub mod pallet;
+pub mod style;
+pub mod theme;
+
+use crate::pages::Transition;
 
 #[derive(Debug)]
 pub struct Config {
     pub layout: layout::Layout,
     pub pallet: pallet::Pallet,
+    pub style: style::Style,
     pub controls: controls::Controls,
+    /// Animation between pages (see [Pages](crate::pages::Pages)).
+    pub transition: Transition,
 }
 
 impl Default for Config {
@@ -14,7 +21,9 @@ impl Default for Config {
         Self {
             layout: layout::Layout::new(100.0, 100.0),
             pallet: pallet::Pallet::default(),
+            style: style::Style::default(),
             controls: controls::Controls::default(),
+            transition: Transition::default(),
         }
     }
 }
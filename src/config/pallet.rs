@@ -1,4 +1,31 @@
-use wut::gx2::color::Color;
+use wut::{gx2::color::Color, vec::Vec};
+
+/// Contrast ratio WCAG 2 (AA) requires for normal text.
+pub const MIN_CONTRAST: f32 = 4.5;
+
+/// Contrast the derived [Pallet::disabled] keeps. WCAG exempts inactive controls, but they should stay legible.
+pub const DISABLED_CONTRAST: f32 = 3.0;
+
+/// WCAG 2 relative luminance of the linearized sRGB color, `0.0` (black) to `1.0` (white). Alpha is ignored.
+pub fn relative_luminance(color: Color) -> f32 {
+    let (r, g, b, _) = color.into();
+    let linear = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            libm::powf((c + 0.055) / 1.055, 2.4)
+        }
+    };
+
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG 2 contrast ratio of two colors, `1.0` (none) to `21.0` (black on white).
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct ColorCombination {
@@ -11,39 +38,285 @@ impl ColorCombination {
         Self { base, content }
     }
 
+    /// Black or white content, whichever contrasts more with `base`.
     pub fn auto(base: Color) -> Self {
-        let (r, g, b, _) = base.into();
-
         Self::new(
             base,
-            if (0.299 * r + 0.587 * g + 0.114 * b) > 0.5 {
+            if contrast_ratio(base, Color::black()) > contrast_ratio(base, Color::white()) {
                 Color::black()
             } else {
                 Color::white()
             },
         )
     }
+
+    pub fn contrast(&self) -> f32 {
+        contrast_ratio(self.base, self.content)
+    }
+
+    /// Nudges the content color towards black or white until it reaches `target` contrast with the base.
+    pub fn fixed(self, target: f32) -> Self {
+        Self::new(self.base, fix(self.content, self.base, target))
+    }
+}
+
+/// Role pair of a [Pallet] with too little contrast, see [Pallet::validate].
+#[derive(Debug, Clone, Copy)]
+pub struct LowContrast {
+    /// Name of the role, e.g. `"highlight"`.
+    pub role: &'static str,
+    pub ratio: f32,
+}
+
+/// State a widget is drawn in, see [Pallet::state].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Normal,
+    Focused,
+    /// Touched and held.
+    Pressed,
+    Disabled,
+}
+
+impl State {
+    /// `disabled` wins over `pressed`, which wins over `focused`.
+    pub fn new(disabled: bool, pressed: bool, focused: bool) -> Self {
+        match (disabled, pressed, focused) {
+            (true, _, _) => Self::Disabled,
+            (_, true, _) => Self::Pressed,
+            (_, _, true) => Self::Focused,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// Curated pallets, see [Pallet::preset].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    #[default]
+    Dark,
+    Light,
+    /// Black and white with a yellow highlight, for low vision.
+    HighContrast,
+    /// Light gray with white widgets and a cyan highlight, like the HOME Menu and System Settings.
+    WiiU,
 }
 
 #[derive(Debug, Clone)]
 pub struct Pallet {
+    /// Color the app clears the screen with, text outside of widgets (e.g. labels) uses its content color.
     pub background: ColorCombination,
     pub widget: ColorCombination,
+    /// Focused widgets.
     pub highlight: ColorCombination,
+    /// Widgets that are touched and held.
+    pub pressed: ColorCombination,
+    /// Widgets that can't be focused or used.
+    pub disabled: ColorCombination,
+    /// Selected entries, e.g. of a list.
+    pub selection: ColorCombination,
+    pub error: ColorCombination,
+    pub warning: ColorCombination,
+    pub success: ColorCombination,
+    /// Outline of widgets.
+    pub border: Color,
+    /// Outline drawn around the focused widget.
+    pub focus_outline: Color,
+    /// Less important text, e.g. hints.
+    pub secondary_text: Color,
+    /// Drop shadow under popups. The built-in widgets only cast it under
+    /// [hover text](crate::widgets::Response::on_hover_text), panels of the app can use it with [Ui::drop_shadow](crate::Ui::drop_shadow).
+    pub shadow: Color,
 }
 
 impl Pallet {
+    /// Derives all roles from the three main colors.
     pub fn auto(background: Color, widget: Color, highlight: Color) -> Self {
+        let background = ColorCombination::auto(background);
+
         Self {
-            background: ColorCombination::auto(background),
             widget: ColorCombination::auto(widget),
             highlight: ColorCombination::auto(highlight),
+            pressed: ColorCombination::auto(mix(highlight, Color::black(), 0.3)),
+            disabled: disabled(widget, background.base),
+            selection: ColorCombination::auto(mix(highlight, background.base, 0.5)),
+            error: ColorCombination::auto(Color::new(0.8, 0.1, 0.1, 1.0)),
+            warning: ColorCombination::auto(Color::new(0.9, 0.6, 0.0, 1.0)),
+            success: ColorCombination::auto(Color::new(0.1, 0.6, 0.2, 1.0)),
+            border: mix(widget, background.content, 0.5),
+            focus_outline: background.content,
+            secondary_text: mix(background.content, background.base, 0.4),
+            shadow: Color::new(0.0, 0.0, 0.0, 0.5),
+            background,
+        }
+    }
+
+    pub fn preset(preset: Preset) -> Self {
+        let hex = |rgb: u32| {
+            let channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+            Color::new(channel(16), channel(8), channel(0), 1.0)
+        };
+
+        match preset {
+            Preset::Dark => Self::auto(hex(0x1e1e1e), hex(0x3a3a3a), hex(0x1e6fc0)),
+            Preset::Light => Self::auto(hex(0xf2f2f2), hex(0xdcdcdc), hex(0x1565c0)),
+            Preset::HighContrast => Self {
+                selection: ColorCombination::auto(Color::white()),
+                border: Color::white(),
+                focus_outline: hex(0xffff00),
+                secondary_text: Color::white(),
+                ..Self::auto(Color::black(), Color::black(), hex(0xffff00))
+            },
+            Preset::WiiU => Self {
+                focus_outline: hex(0x00aaee),
+                ..Self::auto(hex(0xe6e6e6), Color::white(), hex(0x0077b3))
+            },
+        }
+    }
+
+    /// Role pairs with text below [MIN_CONTRAST]. [Pallet::disabled] is left out, WCAG exempts inactive controls
+    /// (see [DISABLED_CONTRAST]).
+    pub fn validate(&self) -> Vec<LowContrast> {
+        let mut pairs: Vec<_> = [
+            ("background", self.background),
+            ("widget", self.widget),
+            ("highlight", self.highlight),
+            ("pressed", self.pressed),
+            ("selection", self.selection),
+            ("error", self.error),
+            ("warning", self.warning),
+            ("success", self.success),
+        ]
+        .into_iter()
+        .map(|(role, combination)| (role, combination.contrast()))
+        .collect();
+        pairs.push((
+            "secondary_text",
+            contrast_ratio(self.secondary_text, self.background.base),
+        ));
+
+        pairs
+            .into_iter()
+            .filter(|(_, ratio)| *ratio < MIN_CONTRAST)
+            .map(|(role, ratio)| LowContrast { role, ratio })
+            .collect()
+    }
+
+    /// Nudges the content colors of all pairs [Pallet::validate] checks until they reach `target` contrast, e.g.
+    /// [MIN_CONTRAST].
+    pub fn fix(&mut self, target: f32) {
+        for combination in [
+            &mut self.background,
+            &mut self.widget,
+            &mut self.highlight,
+            &mut self.pressed,
+            &mut self.selection,
+            &mut self.error,
+            &mut self.warning,
+            &mut self.success,
+        ] {
+            *combination = combination.fixed(target);
+        }
+
+        self.secondary_text = fix(self.secondary_text, self.background.base, target);
+    }
+
+    /// Colors of a widget in `state`.
+    pub fn state(&self, state: State) -> ColorCombination {
+        match state {
+            State::Normal => self.widget,
+            State::Focused => self.highlight,
+            State::Pressed => self.pressed,
+            State::Disabled => self.disabled,
         }
     }
 }
 
 impl Default for Pallet {
     fn default() -> Self {
-        Self::auto(Color::black(), Color::black(), Color::red())
+        Self::preset(Preset::default())
+    }
+}
+
+/// Widget faded halfway into the background, with its content faded as far as [DISABLED_CONTRAST] allows.
+fn disabled(widget: Color, background: Color) -> ColorCombination {
+    let base = mix(widget, background, 0.5);
+    let content = mix(ColorCombination::auto(base).content, base, 0.5);
+
+    ColorCombination::new(base, content).fixed(DISABLED_CONTRAST)
+}
+
+/// Moves `content` towards white if it is lighter than `base` and towards black otherwise, in small steps until it
+/// reaches `target` contrast. Tries the other way if that is not enough, black or white is the best it can do.
+fn fix(content: Color, base: Color, target: f32) -> Color {
+    const STEP: f32 = 0.05;
+
+    if contrast_ratio(content, base) >= target {
+        return content;
+    }
+
+    let lighter = relative_luminance(content) > relative_luminance(base);
+    let extremes = if lighter {
+        [Color::white(), Color::black()]
+    } else {
+        [Color::black(), Color::white()]
+    };
+
+    for extreme in extremes {
+        let mut t = STEP;
+        while t < 1.0 {
+            let nudged = mix(content, extreme, t);
+            if contrast_ratio(nudged, base) >= target {
+                return nudged;
+            }
+            t += STEP;
+        }
+    }
+
+    ColorCombination::auto(base).content
+}
+
+/// Blends `a` towards `b` by `t` (`0.0` = `a`).
+pub(crate) fn mix(a: Color, b: Color, t: f32) -> Color {
+    let (ar, ag, ab, aa) = a.into();
+    let (br, bg, bb, ba) = b.into();
+
+    Color::new(
+        ar + (br - ar) * t,
+        ag + (bg - ag) * t,
+        ab + (bb - ab) * t,
+        aa + (ba - aa) * t,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [Preset; 4] = [
+        Preset::Dark,
+        Preset::Light,
+        Preset::HighContrast,
+        Preset::WiiU,
+    ];
+
+    #[test]
+    fn presets_are_readable() {
+        for preset in PRESETS {
+            let pallet = Pallet::preset(preset);
+
+            assert!(
+                pallet.validate().is_empty(),
+                "{:?}: {:?}",
+                preset,
+                pallet.validate()
+            );
+            assert!(
+                pallet.disabled.contrast() >= DISABLED_CONTRAST,
+                "{:?}: disabled {}",
+                preset,
+                pallet.disabled.contrast()
+            );
+        }
     }
 }
@@ -0,0 +1,85 @@
+use wut::gx2::types::Vec2;
+
+/// Font sizes in pixels for each text role.
+#[derive(Debug, Clone, Copy)]
+pub struct FontSizes {
+    /// [Headings](crate::Ui::heading).
+    pub heading: usize,
+    /// Labels and widget text.
+    pub body: usize,
+    /// Tooltips and hints.
+    pub small: usize,
+}
+
+impl Default for FontSizes {
+    fn default() -> Self {
+        Self {
+            heading: 48,
+            body: 32,
+            small: 24,
+        }
+    }
+}
+
+/// How the focused widget is marked.
+#[derive(Debug, Clone, Copy)]
+pub struct FocusIndicator {
+    /// Fill the widget with [Pallet::highlight](super::pallet::Pallet::highlight).
+    pub highlight: bool,
+    /// Width of the [Pallet::focus_outline](super::pallet::Pallet::focus_outline), `0.0` for none.
+    pub outline: f32,
+    /// Space between the widget and the outline.
+    pub outline_gap: f32,
+}
+
+impl Default for FocusIndicator {
+    fn default() -> Self {
+        Self {
+            highlight: true,
+            outline: 3.0,
+            outline_gap: 0.0,
+        }
+    }
+}
+
+/// Sizes shared by all built-in widgets.
+#[derive(Debug, Clone)]
+pub struct Style {
+    /// Space between a widget's background and its content.
+    pub padding: Vec2<f32>,
+    /// Space between widgets.
+    pub spacing: Vec2<f32>,
+    /// Corner radius of widget backgrounds.
+    pub rounding: f32,
+    /// Segments per full circle for rounded corners and other curves (see [shapes](crate::shapes)).
+    pub segments: usize,
+    /// Blur of the [Pallet::shadow](super::pallet::Pallet::shadow) under popups (hover text), `0.0` for none.
+    pub shadow: f32,
+    /// Width of the [Pallet::border](super::pallet::Pallet::border) around widgets, `0.0` for none.
+    pub border: f32,
+    pub font: FontSizes,
+    pub focus: FocusIndicator,
+    /// Seconds focus color fades and the focus outline take to follow the focus, `0.0` to switch instantly.
+    pub animation: f32,
+    /// Minimum digits (including the decimal point) and decimals of numbers, e.g. `5` and `2` for `01.50`.
+    pub number_width: usize,
+    pub number_precision: usize,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            padding: Vec2::new(10.0, 10.0),
+            spacing: Vec2::new(10.0, 10.0),
+            rounding: 0.0,
+            segments: 32,
+            shadow: 12.0,
+            border: 0.0,
+            font: FontSizes::default(),
+            focus: FocusIndicator::default(),
+            animation: 0.12,
+            number_width: 5,
+            number_precision: 2,
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Themes as text, e.g. to tweak colors on the SD card without recompiling.
+//!
+//! A theme has a `[pallet]`, `[style]` and `[controls]` section with `key = value` lines. Colors are `#rrggbb` or
+//! `#rrggbbaa`, color combinations are a base color optionally followed by a content color (otherwise derived with
+//! [ColorCombination::auto]). `preset = dark|light|high_contrast|wii_u` starts the pallet from a [Preset].
+//! `[controls]` takes the lines of [Controls::save_bindings](super::controls::Controls::save_bindings).
+//!
+//! ```text
+//! [pallet]
+//! preset = wii_u
+//! background = #000000
+//! highlight = #ff0000, #ffffff
+//!
+//! [style]
+//! padding = 10, 10
+//! font.body = 32
+//!
+//! [controls]
+//! gamepad.Accept = A
+//! ```
+
+use super::{
+    Config,
+    pallet::{ColorCombination, Pallet, Preset},
+    style::Style,
+};
+use crate::{GuiiError, font::markup::parse_hex};
+use alloc::string::String;
+use core::{fmt::Write, str::FromStr};
+use wut::{
+    format,
+    gx2::{color::Color, types::Vec2},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Section {
+    Pallet,
+    Style,
+    Controls,
+}
+
+impl Config {
+    /// Writes the pallet, style and control bindings as a theme (see [theme](self)).
+    pub fn save_theme(&self) -> String {
+        let mut out = String::from("[pallet]\n");
+        save_pallet(&self.pallet, &mut out);
+
+        out.push_str("\n[style]\n");
+        save_style(&self.style, &mut out);
+
+        out.push_str("\n[controls]\n");
+        out.push_str(&self.controls.save_bindings());
+
+        out
+    }
+
+    /// Loads a theme written by [Config::save_theme]. Keys that are not listed keep their value. Empty lines and
+    /// lines starting with `#` are ignored.
+    pub fn load_theme(&mut self, text: &str) -> Result<(), GuiiError> {
+        let mut section = None;
+
+        for (i, line) in text.lines().enumerate() {
+            self.load_line(line.trim(), &mut section)
+                .map_err(|message| GuiiError::Parse {
+                    line: i + 1,
+                    message,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads a theme file, `sd:/` is resolved to the SD card.
+    pub fn load_theme_file(&mut self, path: &str) -> Result<(), GuiiError> {
+        let path = crate::font::source::resolve(path);
+        let data = wut::fs::read(&path).map_err(|source| GuiiError::ThemeFile {
+            path: path.clone(),
+            source,
+        })?;
+        let text = core::str::from_utf8(&data).map_err(|error| GuiiError::Parse {
+            line: 1 + data[..error.valid_up_to()]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count(),
+            message: String::from("invalid UTF-8"),
+        })?;
+
+        self.load_theme(text)
+    }
+
+    fn load_line(&mut self, line: &str, section: &mut Option<Section>) -> Result<(), String> {
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            *section = Some(match name.trim() {
+                "pallet" => Section::Pallet,
+                "style" => Section::Style,
+                "controls" => Section::Controls,
+                name => return Err(format!("unknown section `[{}]`", name)),
+            });
+            return Ok(());
+        }
+
+        let section = section.ok_or_else(|| String::from("expected a `[section]` first"))?;
+
+        if let Section::Controls = section {
+            return self.controls.load_line(line);
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| String::from("expected `key = value`"))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        match section {
+            Section::Pallet => load_pallet(&mut self.pallet, key, value),
+            Section::Style => load_style(&mut self.style, key, value),
+            Section::Controls => unreachable!(),
+        }
+    }
+}
+
+fn save_pallet(pallet: &Pallet, out: &mut String) {
+    let combinations = [
+        ("background", pallet.background),
+        ("widget", pallet.widget),
+        ("highlight", pallet.highlight),
+        ("pressed", pallet.pressed),
+        ("disabled", pallet.disabled),
+        ("selection", pallet.selection),
+        ("error", pallet.error),
+        ("warning", pallet.warning),
+        ("success", pallet.success),
+    ];
+
+    for (key, combination) in combinations {
+        let _ = writeln!(
+            out,
+            "{} = {}, {}",
+            key,
+            hex(combination.base),
+            hex(combination.content)
+        );
+    }
+
+    for (key, color) in [
+        ("border", pallet.border),
+        ("focus_outline", pallet.focus_outline),
+        ("secondary_text", pallet.secondary_text),
+        ("shadow", pallet.shadow),
+    ] {
+        let _ = writeln!(out, "{} = {}", key, hex(color));
+    }
+}
+
+fn load_pallet(pallet: &mut Pallet, key: &str, value: &str) -> Result<(), String> {
+    let combination = match key {
+        // replaces the whole pallet, so it should come first
+        "preset" => {
+            *pallet = Pallet::preset(match value {
+                "dark" => Preset::Dark,
+                "light" => Preset::Light,
+                "high_contrast" => Preset::HighContrast,
+                "wii_u" => Preset::WiiU,
+                value => return Err(format!("unknown preset `{}`", value)),
+            });
+            return Ok(());
+        }
+        "background" => &mut pallet.background,
+        "widget" => &mut pallet.widget,
+        "highlight" => &mut pallet.highlight,
+        "pressed" => &mut pallet.pressed,
+        "disabled" => &mut pallet.disabled,
+        "selection" => &mut pallet.selection,
+        "error" => &mut pallet.error,
+        "warning" => &mut pallet.warning,
+        "success" => &mut pallet.success,
+        _ => {
+            let color = match key {
+                "border" => &mut pallet.border,
+                "focus_outline" => &mut pallet.focus_outline,
+                "secondary_text" => &mut pallet.secondary_text,
+                "shadow" => &mut pallet.shadow,
+                key => return Err(format!("unknown pallet key `{}`", key)),
+            };
+
+            *color = parse_color(value)?;
+            return Ok(());
+        }
+    };
+
+    *combination = match value.split_once(',') {
+        Some((base, content)) => {
+            ColorCombination::new(parse_color(base.trim())?, parse_color(content.trim())?)
+        }
+        None => ColorCombination::auto(parse_color(value)?),
+    };
+
+    Ok(())
+}
+
+fn save_style(style: &Style, out: &mut String) {
+    let _ = writeln!(out, "padding = {}, {}", style.padding.x, style.padding.y);
+    let _ = writeln!(out, "spacing = {}, {}", style.spacing.x, style.spacing.y);
+    let _ = writeln!(out, "rounding = {}", style.rounding);
+    let _ = writeln!(out, "segments = {}", style.segments);
+    let _ = writeln!(out, "shadow = {}", style.shadow);
+    let _ = writeln!(out, "border = {}", style.border);
+    let _ = writeln!(out, "font.heading = {}", style.font.heading);
+    let _ = writeln!(out, "font.body = {}", style.font.body);
+    let _ = writeln!(out, "font.small = {}", style.font.small);
+    let _ = writeln!(out, "focus.highlight = {}", style.focus.highlight);
+    let _ = writeln!(out, "focus.outline = {}", style.focus.outline);
+    let _ = writeln!(out, "focus.outline_gap = {}", style.focus.outline_gap);
+    let _ = writeln!(out, "animation = {}", style.animation);
+    let _ = writeln!(out, "number.width = {}", style.number_width);
+    let _ = writeln!(out, "number.precision = {}", style.number_precision);
+}
+
+fn load_style(style: &mut Style, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "padding" => style.padding = parse_vec2(value)?,
+        "spacing" => style.spacing = parse_vec2(value)?,
+        "rounding" => style.rounding = parse(value)?,
+        "segments" => style.segments = parse(value)?,
+        "shadow" => style.shadow = parse(value)?,
+        "border" => style.border = parse(value)?,
+        "font.heading" => style.font.heading = parse(value)?,
+        "font.body" => style.font.body = parse(value)?,
+        "font.small" => style.font.small = parse(value)?,
+        "focus.highlight" => style.focus.highlight = parse(value)?,
+        "focus.outline" => style.focus.outline = parse(value)?,
+        "focus.outline_gap" => style.focus.outline_gap = parse(value)?,
+        "animation" => style.animation = parse(value)?,
+        "number.width" => style.number_width = parse(value)?,
+        "number.precision" => style.number_precision = parse(value)?,
+        key => return Err(format!("unknown style key `{}`", key)),
+    }
+
+    Ok(())
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}`", value))
+}
+
+fn parse_vec2(value: &str) -> Result<Vec2<f32>, String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected `x, y`, found `{}`", value))?;
+
+    Ok(Vec2::new(parse(x.trim())?, parse(y.trim())?))
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    value
+        .strip_prefix('#')
+        .and_then(parse_hex)
+        .ok_or_else(|| format!("expected `#rrggbb` or `#rrggbbaa`, found `{}`", value))
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b, a) = color.into();
+    let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+
+    if a >= 1.0 {
+        format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b))
+    } else {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            byte(r),
+            byte(g),
+            byte(b),
+            byte(a)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line and message of the error loading `text` into the default config.
+    fn error(text: &str) -> (usize, String) {
+        match Config::default().load_theme(text) {
+            Err(GuiiError::Parse { line, message }) => (line, message),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut config = Config {
+            pallet: Pallet::preset(Preset::WiiU),
+            ..Config::default()
+        };
+        config.style.padding = Vec2::new(4.0, 6.0);
+        config.style.font.body = 28;
+        config.style.focus.highlight = false;
+
+        let saved = config.save_theme();
+        let mut loaded = Config::default();
+        loaded.load_theme(&saved).unwrap();
+
+        assert_eq!(loaded.save_theme(), saved);
+    }
+
+    #[test]
+    fn keys_that_are_not_listed_are_kept() {
+        let mut config = Config::default();
+        config
+            .load_theme("[style]\nfont.small = 20\n\n# comment\n[pallet]\nborder = #ff0000")
+            .unwrap();
+
+        assert_eq!(config.style.font.small, 20);
+        assert_eq!(config.style.font.body, Style::default().font.body);
+        let border: (f32, f32, f32, f32) = config.pallet.border.into();
+        assert_eq!(border, (1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert_eq!(
+            error("[style]\npadding = 1, 2\nmargin = 3"),
+            (3, String::from("unknown style key `margin`"))
+        );
+    }
+
+    #[test]
+    fn unknown_section() {
+        assert_eq!(
+            error("\n[colors]"),
+            (2, String::from("unknown section `[colors]`"))
+        );
+    }
+
+    #[test]
+    fn bad_color() {
+        assert_eq!(error("[pallet]\nborder = #ff00").0, 2);
+        assert_eq!(error("[pallet]\nwidget = #000000, red").0, 2);
+    }
+
+    #[test]
+    fn key_before_section() {
+        assert_eq!(
+            error("# theme\nrounding = 4"),
+            (2, String::from("expected a `[section]` first"))
+        );
+    }
+}
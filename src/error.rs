@@ -1,6 +1,9 @@
+use crate::font::cache::CacheError;
+use alloc::string::String;
 use thiserror::Error;
 use wut::{
     font::FontError,
+    fs::FsError,
     gx2::{buffer::BufferError, shader::ShaderError},
 };
 
@@ -10,8 +13,22 @@ pub enum GuiiError {
     BufferError(#[from] BufferError),
     #[error("Creating the ")]
     ShaderError(#[from] ShaderError),
-    #[error("Fontdue")]
+    #[error("Fontdue: {0}")]
     FontdueError(&'static str),
+    #[error("Invalid font data: {0}")]
+    InvalidFont(&'static str),
+    #[error("Invalid font file `{path}`: {reason}")]
+    InvalidFontFile { path: String, reason: &'static str },
+    #[error("Reading font file `{path}` failed: {source}")]
+    FontFile { path: String, source: FsError },
     #[error("System font")]
     FontError(#[from] FontError),
+    #[error("Font chain needs at least one font")]
+    EmptyFontChain,
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Reading theme file `{path}` failed: {source}")]
+    ThemeFile { path: String, source: FsError },
+    #[error("Font atlus cache: {0}")]
+    Cache(#[from] CacheError),
 }
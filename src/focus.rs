@@ -1,23 +1,194 @@
-pub struct Focus(usize);
+use crate::{
+    config::controls::{Action, Navigation},
+    rect::Rect,
+};
+use wut::vec::Vec;
+
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    enabled: bool,
+    rect: Rect,
+}
+
+/// Keyboard-style focus over the focusable widgets of a frame.
+///
+/// Widgets are identified by the order they are added in (their ID, see [Ui::next_id](crate::Ui::next_id)). Navigation uses the widgets recorded in the previous
+/// frame, so disabled widgets can be skipped before they are drawn.
+pub struct Focus {
+    index: usize,
+    /// Last widget that was drawn with the focus, to tell which widget gained or lost it.
+    reported: Option<usize>,
+    /// Focus requested by the app, applied at the end of the frame.
+    pub(crate) request: Option<usize>,
+    /// Widgets of the previous frame.
+    items: Vec<Item>,
+    recording: Vec<Item>,
+    /// Wrap from the last widget to the first and back.
+    pub(crate) wrap: bool,
+    pub(crate) navigation: Navigation,
+}
 
 impl Focus {
     pub fn new() -> Self {
-        Self(0)
+        Self {
+            index: 0,
+            reported: None,
+            request: None,
+            items: Vec::new(),
+            recording: Vec::new(),
+            wrap: false,
+            navigation: Navigation::Linear,
+        }
+    }
+
+    fn enabled(&self, index: usize) -> bool {
+        self.items[index].enabled
     }
 
     pub fn next(&mut self) {
-        self.0 = self.0.saturating_add(1);
+        let after = (self.index + 1..self.items.len()).find(|i| self.enabled(*i));
+        let wrapped = || (0..self.index).find(|i| self.enabled(*i));
+
+        if let Some(index) = after.or_else(|| self.wrap.then(wrapped).flatten()) {
+            self.index = index;
+        }
     }
 
     pub fn prev(&mut self) {
-        self.0 = self.0.saturating_sub(1);
+        let before = (0..self.index.min(self.items.len()))
+            .rev()
+            .find(|i| self.enabled(*i));
+        let wrapped = || {
+            (self.index + 1..self.items.len())
+                .rev()
+                .find(|i| self.enabled(*i))
+        };
+
+        if let Some(index) = before.or_else(|| self.wrap.then(wrapped).flatten()) {
+            self.index = index;
+        }
+    }
+
+    /// Focuses the first enabled widget.
+    pub fn first(&mut self) {
+        if let Some(index) = self.items.iter().position(|item| item.enabled) {
+            self.index = index;
+        }
+    }
+
+    /// Focuses the last enabled widget.
+    pub fn last(&mut self) {
+        if let Some(index) = self.items.iter().rposition(|item| item.enabled) {
+            self.index = index;
+        }
+    }
+
+    /// Moves the focus in `direction` (see [Navigation]). Returns whether the direction is used for navigation, so
+    /// unused Left/Right stay pending: they are only used in [Navigation::Spatial] when a widget lies that way.
+    pub fn navigate(&mut self, direction: Action) -> bool {
+        if self.navigation == Navigation::Spatial
+            && let Some(index) = self.closest(direction)
+        {
+            self.index = index;
+            return true;
+        }
+
+        match direction {
+            Action::Up => self.prev(),
+            Action::Down => self.next(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Closest enabled widget in `direction` of the focused one. Distance across the direction counts double, so
+    /// widgets in the same row or column win over diagonal ones.
+    fn closest(&self, direction: Action) -> Option<usize> {
+        let from = self.items.get(self.index)?.rect;
+        let center = from.center();
+
+        // distance between two ranges, 0 if they overlap
+        let gap = |a: f32, a_len: f32, b: f32, b_len: f32| {
+            (b - (a + a_len)).max(a - (b + b_len)).max(0.0)
+        };
+
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(i, item)| *i != self.index && item.enabled)
+            .filter_map(|(i, item)| {
+                let to = item.rect;
+                let horizontal = gap(from.x, from.w, to.x, to.w);
+                let vertical = gap(from.y, from.h, to.y, to.h);
+
+                let (along, across) = match direction {
+                    Action::Up => (to.center().y - center.y, horizontal),
+                    Action::Down => (center.y - to.center().y, horizontal),
+                    Action::Left => (center.x - to.center().x, vertical),
+                    Action::Right => (to.center().x - center.x, vertical),
+                    _ => return None,
+                };
+
+                (along > 0.0).then_some((i, along + across * 2.0))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
     }
 
     pub fn focused(&self, index: usize) -> bool {
-        self.0 == index
+        self.index == index
+    }
+
+    /// Records whether widget `index` is drawn with the focus. Returns whether it was the last widget drawn with
+    /// the focus, so focus changes are reported once even if they happen after the widget was drawn.
+    pub(crate) fn report(&mut self, index: usize, focused: bool) -> bool {
+        let was_focused = self.reported == Some(index);
+
+        if focused {
+            self.reported = Some(index);
+        } else if was_focused {
+            self.reported = None;
+        }
+
+        was_focused
+    }
+
+    /// Forgets the last focused widget, e.g. when another page opens and the IDs mean other widgets.
+    pub(crate) fn forget(&mut self) {
+        self.reported = None;
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn set(&mut self, index: usize) {
+        self.index = index;
     }
 
-    pub(crate) fn clamp(&mut self, min: usize, max: usize) {
-        self.0 = self.0.clamp(min, max);
+    /// Records the next widget of this frame.
+    pub(crate) fn record(&mut self, enabled: bool, rect: Rect) {
+        self.recording.push(Item { enabled, rect });
+    }
+
+    /// Applies a requested focus, swaps in the widgets recorded this frame and moves the focus off missing or disabled widgets.
+    pub(crate) fn end_frame(&mut self) {
+        if let Some(index) = self.request.take() {
+            self.index = index;
+        }
+
+        core::mem::swap(&mut self.items, &mut self.recording);
+        self.recording.clear();
+
+        if self.index >= self.items.len() {
+            self.last();
+        } else if !self.enabled(self.index) {
+            let index = self.index;
+            self.next();
+            if self.index == index {
+                self.prev();
+            }
+        }
     }
 }
@@ -0,0 +1,419 @@
+//! Binary format for prerasterized font atluses.
+//!
+//! Rasterizing glyphs at boot is slow on the Espresso, so an atlus can be rasterized once (on the console or with
+//! `tools/atlus-gen` on the host), stored with [AtlusData::encode] and loaded with [Atlus::from_cache](super::Atlus::from_cache).
+//!
+//! This module only depends on `alloc` and `fontdue` so the host generator can share it.
+//!
+//! Layout (little endian):
+//!
+//! | Field        | Size                   |
+//! | ------------ | ---------------------- |
+//! | magic `GATL` | 4                      |
+//! | version      | 2                      |
+//! | reserved     | 2                      |
+//! | hash         | 4 (FNV-1a of the rest) |
+//! | charset hash | 4                      |
+//! | px           | 4                      |
+//! | width        | 4                      |
+//! | height       | 4                      |
+//! | glyph count  | 4                      |
+//! | glyphs       | count * 56             |
+//! | bitmap       | width * height         |
+
+use alloc::vec::Vec;
+use fontdue::{Metrics, OutlineBounds};
+use thiserror::Error;
+
+pub const MAGIC: [u8; 4] = *b"GATL";
+pub const VERSION: u16 = 1;
+
+const HEADER: usize = 32;
+const GLYPH: usize = 56;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CacheError {
+    #[error("Not a font atlus cache")]
+    Magic,
+    #[error("Unsupported atlus cache version {0} (expected {VERSION})")]
+    Version(u16),
+    #[error("Atlus cache is corrupted (hash mismatch)")]
+    Hash,
+    #[error("Atlus cache is truncated")]
+    Truncated,
+    #[error("Atlus cache contains invalid char {0:#x}")]
+    InvalidChar(u32),
+    #[error("Atlus cache was rasterized at {0}px")]
+    Px(usize),
+    #[error("Atlus cache was built for a different charset")]
+    Charset,
+}
+
+/// Position and metrics of a rasterized glyph.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub c: char,
+    /// Glyph belongs to the emphasis face.
+    pub emphasis: bool,
+    /// Top left pixel in the bitmap.
+    pub x: usize,
+    pub y: usize,
+    pub metrics: Metrics,
+}
+
+/// A fully rasterized atlus.
+#[derive(Debug, Clone)]
+pub struct AtlusData {
+    pub charset: u32,
+    pub px: usize,
+    pub width: usize,
+    pub height: usize,
+    pub glyphs: Vec<Glyph>,
+    /// Single channel coverage, `width * height` bytes.
+    pub bitmap: Vec<u8>,
+}
+
+impl AtlusData {
+    /// Rasterizes `chars` into cells of `px * px` on a bitmap `pitch` pixels wide.
+    ///
+    /// `regular` (and `emphasis`) pick the font for each char.
+    pub fn rasterize<'f>(
+        chars: &[char],
+        px: usize,
+        pitch: usize,
+        regular: &dyn Fn(char) -> &'f fontdue::Font,
+        emphasis: Option<&dyn Fn(char) -> &'f fontdue::Font>,
+    ) -> Self {
+        let mut jobs = Vec::with_capacity(chars.len() * 2);
+        jobs.extend(chars.iter().map(|c| (false, *c, regular(*c))));
+        if let Some(emphasis) = emphasis {
+            jobs.extend(chars.iter().map(|c| (true, *c, emphasis(*c))));
+        }
+
+        let columns = pitch / px;
+        let rows = jobs.len().div_ceil(columns);
+
+        let width = columns * px;
+        let height = rows * px;
+
+        let mut bitmap = alloc::vec![0; width * height];
+        let mut glyphs = Vec::with_capacity(jobs.len());
+
+        for (i, (emphasis, c, font)) in jobs.into_iter().enumerate() {
+            let (metrics, raster) = font.rasterize(c, px as f32);
+
+            let start_x = (i % columns) * px;
+            let start_y = (i / columns) * px;
+
+            for y in 0..metrics.height {
+                let dst = (start_y + y) * width + start_x;
+                let src = y * metrics.width;
+
+                bitmap[dst..dst + metrics.width].copy_from_slice(&raster[src..src + metrics.width]);
+            }
+
+            glyphs.push(Glyph {
+                c,
+                emphasis,
+                x: start_x,
+                y: start_y,
+                metrics,
+            });
+        }
+
+        Self {
+            charset: charset_hash(chars),
+            px,
+            width,
+            height,
+            glyphs,
+            bitmap,
+        }
+    }
+
+    /// Whether this atlus was rasterized for `chars` (same chars in the same order).
+    pub fn matches(&self, chars: &[char]) -> bool {
+        self.charset == charset_hash(chars)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER + self.glyphs.len() * GLYPH + self.bitmap.len());
+
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes()); // hash, filled in below
+        out.extend_from_slice(&self.charset.to_le_bytes());
+        for v in [self.px, self.width, self.height, self.glyphs.len()] {
+            out.extend_from_slice(&(v as u32).to_le_bytes());
+        }
+
+        for glyph in &self.glyphs {
+            let m = &glyph.metrics;
+
+            out.push(glyph.emphasis as u8);
+            out.extend_from_slice(&[0; 3]);
+            for v in [glyph.c as u32, glyph.x as u32, glyph.y as u32] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            for v in [m.xmin, m.ymin, m.width as i32, m.height as i32] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            for v in [
+                m.advance_width,
+                m.advance_height,
+                m.bounds.xmin,
+                m.bounds.ymin,
+                m.bounds.width,
+                m.bounds.height,
+            ] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+
+        out.extend_from_slice(&self.bitmap);
+
+        let hash = fnv1a(&out[12..]);
+        out[8..12].copy_from_slice(&hash.to_le_bytes());
+
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, CacheError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err(CacheError::Magic);
+        }
+
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != VERSION {
+            return Err(CacheError::Version(version));
+        }
+        reader.take(2)?;
+
+        let hash = reader.u32()?;
+        if hash != fnv1a(&bytes[12..]) {
+            return Err(CacheError::Hash);
+        }
+
+        let charset = reader.u32()?;
+        let px = reader.u32()? as usize;
+        let width = reader.u32()? as usize;
+        let height = reader.u32()? as usize;
+        let count = reader.u32()? as usize;
+
+        // the sizes come from the file, so a corrupted one must not overflow
+        let size = count
+            .checked_mul(GLYPH)
+            .zip(width.checked_mul(height))
+            .and_then(|(glyphs, bitmap)| HEADER.checked_add(glyphs)?.checked_add(bitmap));
+        if size != Some(bytes.len()) {
+            return Err(CacheError::Truncated);
+        }
+
+        let mut glyphs = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let emphasis = reader.take(4)?[0] != 0;
+            let c = reader.u32()?;
+            let c = char::from_u32(c).ok_or(CacheError::InvalidChar(c))?;
+            let x = reader.u32()? as usize;
+            let y = reader.u32()? as usize;
+
+            let metrics = Metrics {
+                xmin: reader.i32()?,
+                ymin: reader.i32()?,
+                width: reader.u32()? as usize,
+                height: reader.u32()? as usize,
+                advance_width: reader.f32()?,
+                advance_height: reader.f32()?,
+                bounds: OutlineBounds {
+                    xmin: reader.f32()?,
+                    ymin: reader.f32()?,
+                    width: reader.f32()?,
+                    height: reader.f32()?,
+                },
+            };
+
+            let inside = |start: usize, length: usize, end: usize| {
+                start.checked_add(length).is_some_and(|last| last <= end)
+            };
+            if !inside(x, metrics.width, width) || !inside(y, metrics.height, height) {
+                return Err(CacheError::Truncated);
+            }
+
+            glyphs.push(Glyph {
+                c,
+                emphasis,
+                x,
+                y,
+                metrics,
+            });
+        }
+
+        Ok(Self {
+            charset,
+            px,
+            width,
+            height,
+            glyphs,
+            bitmap: Vec::from(reader.take(width * height)?),
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CacheError> {
+        let end = self.pos.checked_add(n).ok_or(CacheError::Truncated)?;
+        let slice = self.bytes.get(self.pos..end).ok_or(CacheError::Truncated)?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CacheError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, CacheError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, CacheError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, b| {
+        (hash ^ *b as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Hash identifying a charset, stored in the cache to detect stale files.
+pub fn charset_hash(chars: &[char]) -> u32 {
+    let bytes: Vec<u8> = chars
+        .iter()
+        .flat_map(|c| (*c as u32).to_le_bytes())
+        .collect();
+    fnv1a(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atlus() -> AtlusData {
+        let metrics = Metrics {
+            xmin: -1,
+            ymin: 2,
+            width: 3,
+            height: 2,
+            advance_width: 4.5,
+            advance_height: 0.0,
+            bounds: OutlineBounds {
+                xmin: -1.0,
+                ymin: 2.0,
+                width: 3.0,
+                height: 2.0,
+            },
+        };
+
+        AtlusData {
+            charset: charset_hash(&['a', 'é']),
+            px: 4,
+            width: 8,
+            height: 4,
+            glyphs: alloc::vec![
+                Glyph {
+                    c: 'a',
+                    emphasis: false,
+                    x: 0,
+                    y: 0,
+                    metrics,
+                },
+                Glyph {
+                    c: 'é',
+                    emphasis: true,
+                    x: 4,
+                    y: 0,
+                    metrics,
+                },
+            ],
+            bitmap: (0..32).collect(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let atlus = atlus();
+        let decoded = AtlusData::decode(&atlus.encode()).unwrap();
+
+        assert_eq!(decoded.charset, atlus.charset);
+        assert_eq!(decoded.px, atlus.px);
+        assert_eq!((decoded.width, decoded.height), (atlus.width, atlus.height));
+        assert_eq!(decoded.bitmap, atlus.bitmap);
+        assert_eq!(decoded.glyphs.len(), atlus.glyphs.len());
+        for (decoded, glyph) in decoded.glyphs.iter().zip(&atlus.glyphs) {
+            assert_eq!(
+                (decoded.c, decoded.emphasis, decoded.x, decoded.y),
+                (glyph.c, glyph.emphasis, glyph.x, glyph.y)
+            );
+            assert_eq!(decoded.metrics, glyph.metrics);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        let mut bytes = atlus().encode();
+        bytes.pop();
+        let hash = fnv1a(&bytes[12..]);
+        bytes[8..12].copy_from_slice(&hash.to_le_bytes());
+
+        assert_eq!(
+            AtlusData::decode(&bytes).unwrap_err(),
+            CacheError::Truncated
+        );
+    }
+
+    #[test]
+    fn rejects_huge_sizes() {
+        let mut bytes = atlus().encode();
+        // width and height, their product only overflows where usize is 32 bits (the Espresso), on the host this
+        // covers the size check
+        bytes[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        bytes[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        let hash = fnv1a(&bytes[12..]);
+        bytes[8..12].copy_from_slice(&hash.to_le_bytes());
+
+        assert_eq!(
+            AtlusData::decode(&bytes).unwrap_err(),
+            CacheError::Truncated
+        );
+    }
+
+    #[test]
+    fn rejects_glyphs_outside_the_bitmap() {
+        let mut bytes = atlus().encode();
+        // x and width of the first glyph, their sum overflows where usize is 32 bits
+        let glyph = HEADER;
+        bytes[glyph + 8..glyph + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        bytes[glyph + 24..glyph + 28].copy_from_slice(&u32::MAX.to_le_bytes());
+        let hash = fnv1a(&bytes[12..]);
+        bytes[8..12].copy_from_slice(&hash.to_le_bytes());
+
+        assert_eq!(
+            AtlusData::decode(&bytes).unwrap_err(),
+            CacheError::Truncated
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Inline markup for rich text.
+//!
+//! | Token                    | Effect                                             |
+//! | ------------------------ | -------------------------------------------------- |
+//! | `{A}`, `{DPAD}`, ...     | Inserts a controller icon (see [ICONS])            |
+//! | `{#rrggbb}`/`{#rrggbbaa}`| Colors the following text                          |
+//! | `{*}`                    | Renders the following text with [Face::Emphasis]   |
+//! | `{/}`                    | Closes the innermost color or emphasis span        |
+//! | `{{`, `}}`               | Literal `{` and `}`                                |
+//!
+//! Markup is never rejected. Unknown or unterminated tokens are rendered verbatim and unmatched `{/}` are ignored.
+//!
+//! ```ignore
+//! ui.rich_label("Press {A} to {#ff0000}{*}delete{/}{/} the save");
+//! ```
+
+use super::Face;
+use wut::{gx2::color::Color, vec::Vec};
+
+/// Icon tokens and the atlus glyph they resolve to.
+pub const ICONS: &[(&str, char)] = &[
+    // gamepad
+    ("A", wut::font::icons::gamepad::A),
+    ("B", wut::font::icons::gamepad::B),
+    ("X", wut::font::icons::gamepad::X),
+    ("Y", wut::font::icons::gamepad::Y),
+    ("L", wut::font::icons::gamepad::L),
+    ("R", wut::font::icons::gamepad::R),
+    ("ZL", wut::font::icons::gamepad::ZL),
+    ("ZR", wut::font::icons::gamepad::ZR),
+    ("PLUS", wut::font::icons::wiimote::PLUS),
+    ("MINUS", wut::font::icons::wiimote::MINUS),
+    ("HOME", wut::font::icons::HOME),
+    ("DPAD", wut::font::icons::gamepad::DPAD),
+    ("DPAD_EMPTY", wut::font::icons::gamepad::DPAD_EMPTY),
+    ("UP", wut::font::icons::gamepad::UP),
+    ("DOWN", wut::font::icons::gamepad::DOWN),
+    ("LEFT", wut::font::icons::gamepad::LEFT),
+    ("RIGHT", wut::font::icons::gamepad::RIGHT),
+    ("UP_DOWN", wut::font::icons::gamepad::UP_DOWN),
+    ("LEFT_RIGHT", wut::font::icons::gamepad::LEFT_RIGHT),
+    ("STICK", wut::font::icons::gamepad::STICK),
+    ("STICK_L", wut::font::icons::gamepad::STICK_L),
+    ("STICK_R", wut::font::icons::gamepad::STICK_R),
+    ("STICK_L_PRESS", wut::font::icons::gamepad::STICK_L_PRESS),
+    ("STICK_R_PRESS", wut::font::icons::gamepad::STICK_R_PRESS),
+    ("TV", wut::font::icons::gamepad::TV),
+    // wiimote
+    ("WIIMOTE_A", wut::font::icons::wiimote::A),
+    ("WIIMOTE_B", wut::font::icons::wiimote::B),
+    ("ONE", wut::font::icons::wiimote::ONE),
+    ("TWO", wut::font::icons::wiimote::TWO),
+    ("POWER", wut::font::icons::wiimote::POWER),
+    // nunchuk
+    ("C", wut::font::icons::nunchuk::C),
+    ("Z", wut::font::icons::nunchuk::Z),
+    // misc
+    ("GAMEPAD", wut::font::icons::GAMEPAD),
+    ("WIIMOTE", wut::font::icons::WIIMOTE),
+    ("HELP", wut::font::icons::HELP),
+    ("BACK", wut::font::icons::BACK),
+    ("CLOSE", wut::font::icons::CLOSE),
+    ("CANCEL", wut::font::icons::CANCEL),
+];
+
+/// Looks up an icon token by name, e.g. `"DPAD"`.
+pub fn icon(name: &str) -> Option<char> {
+    ICONS.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+}
+
+/// A single char together with the style it is rendered in.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub c: char,
+    pub face: Face,
+    /// Overrides the color passed to the text call.
+    pub color: Option<Color>,
+}
+
+impl Glyph {
+    pub const fn plain(c: char) -> Self {
+        Self {
+            c,
+            face: Face::Regular,
+            color: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Span {
+    Color(Color),
+    Emphasis,
+}
+
+/// Iterator over the [Glyph]s of a markup string.
+pub struct Markup<'a> {
+    rest: &'a str,
+    spans: Vec<Span>,
+}
+
+impl<'a> Markup<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            rest: text,
+            spans: Vec::new(),
+        }
+    }
+
+    fn glyph(&self, c: char) -> Glyph {
+        let color = self.spans.iter().rev().find_map(|s| match s {
+            Span::Color(color) => Some(*color),
+            Span::Emphasis => None,
+        });
+
+        let face = if self.spans.iter().any(|s| matches!(s, Span::Emphasis)) {
+            Face::Emphasis
+        } else {
+            Face::Regular
+        };
+
+        Glyph { c, face, color }
+    }
+
+    fn literal(&mut self) -> Option<Glyph> {
+        let c = self.rest.chars().next()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        Some(self.glyph(c))
+    }
+}
+
+impl Iterator for Markup<'_> {
+    type Item = Glyph;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.starts_with("{{") || self.rest.starts_with("}}") {
+                self.rest = &self.rest[1..];
+                return self.literal();
+            }
+
+            if !self.rest.starts_with('{') {
+                return self.literal();
+            }
+
+            let Some(end) = self.rest.find('}') else {
+                return self.literal();
+            };
+
+            let token = &self.rest[1..end];
+
+            if let Some(c) = icon(token) {
+                self.rest = &self.rest[end + 1..];
+                return Some(self.glyph(c));
+            }
+
+            match token {
+                "*" => self.spans.push(Span::Emphasis),
+                "/" => {
+                    self.spans.pop();
+                }
+                _ => match token.strip_prefix('#').and_then(parse_hex) {
+                    Some(color) => self.spans.push(Span::Color(color)),
+                    None => return self.literal(),
+                },
+            }
+
+            self.rest = &self.rest[end + 1..];
+        }
+    }
+}
+
+/// Parses `rrggbb` or `rrggbbaa`.
+pub(crate) fn parse_hex(hex: &str) -> Option<Color> {
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap() as f32 / 255.0;
+
+    Some(Color::new(
+        channel(0),
+        channel(2),
+        channel(4),
+        if hex.len() == 8 { channel(6) } else { 1.0 },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn text(markup: &str) -> String {
+        Markup::new(markup).map(|glyph| glyph.c).collect()
+    }
+
+    fn rgba(glyph: &Glyph) -> Option<(f32, f32, f32, f32)> {
+        glyph.color.map(Into::into)
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(text("{{A}} {{"), "{A} {");
+        assert_eq!(text("}}}"), "}}");
+    }
+
+    #[test]
+    fn malformed_tokens_are_verbatim() {
+        assert_eq!(text("{NOPE} x"), "{NOPE} x");
+        assert_eq!(text("{#ff"), "{#ff");
+        assert_eq!(text("{#ff} {#gggggg}"), "{#ff} {#gggggg}");
+        assert_eq!(text("a {"), "a {");
+    }
+
+    #[test]
+    fn stray_close_is_ignored() {
+        assert_eq!(text("{/}a{/}"), "a");
+        assert!(Markup::new("{/}a").all(|glyph| glyph.color.is_none()));
+    }
+
+    #[test]
+    fn icons() {
+        assert_eq!(
+            text("{A}{DPAD}"),
+            String::from_iter([
+                wut::font::icons::gamepad::A,
+                wut::font::icons::gamepad::DPAD
+            ])
+        );
+        assert_eq!(icon("STICK_L"), Some(wut::font::icons::gamepad::STICK_L));
+        assert_eq!(icon("a"), None);
+    }
+
+    #[test]
+    fn spans() {
+        let glyphs: Vec<_> = Markup::new("a{#ff000080}b{*}c{#00ff00}d{/}e{/}f{/}g").collect();
+        let red = Some((1.0, 0.0, 0.0, 128.0 / 255.0));
+        let green = Some((0.0, 1.0, 0.0, 1.0));
+
+        let styles: Vec<_> = glyphs
+            .iter()
+            .map(|glyph| (glyph.c, glyph.face, rgba(glyph)))
+            .collect();
+        assert_eq!(
+            styles,
+            [
+                ('a', Face::Regular, None),
+                ('b', Face::Regular, red),
+                ('c', Face::Emphasis, red),
+                ('d', Face::Emphasis, green),
+                ('e', Face::Emphasis, red),
+                ('f', Face::Regular, red),
+                ('g', Face::Regular, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn icons_take_the_span_style() {
+        let glyph = Markup::new("{#0000ff}{*}{A}").next().unwrap();
+
+        assert_eq!(glyph.c, wut::font::icons::gamepad::A);
+        assert_eq!(glyph.face, Face::Emphasis);
+        assert_eq!(rgba(&glyph), Some((0.0, 0.0, 1.0, 1.0)));
+    }
+}
@@ -1,6 +1,12 @@
+pub mod cache;
+pub mod markup;
+pub mod source;
+
 use crate::{GuiiError, config::layout::Scaling};
+use cache::{AtlusData, CacheError};
+use source::{FontChain, FontSource};
 use core::alloc::GlobalAlloc;
-use fontdue::{self, Metrics};
+use fontdue::{self, FontSettings, Metrics};
 use hashbrown::HashMap;
 use wut::{
     self,
@@ -41,12 +47,34 @@ impl TexCoords {
     }
 }
 
+/// Typeface a glyph is rasterized from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    Regular,
+    /// Secondary font used for emphasized text. Falls back to [Face::Regular] if the atlus has no emphasis font.
+    Emphasis,
+}
+
 /// Font atlus
 ///
 /// All glyphs are prerendered into a single texture and rendered by their location on this texture. Allows for single draw call text rendering.
 pub struct Atlus {
     tex: Texture,
-    coords: HashMap<char, (TexCoords, Metrics)>,
+    coords: HashMap<(Face, char), (TexCoords, Metrics)>,
+    /// Widest advance of the digits `0`-`9` at [Atlus::PX].
+    digit_advance: f32,
+}
+
+/// How glyphs are advanced horizontally.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Spacing {
+    /// Every glyph uses its own advance.
+    #[default]
+    Proportional,
+    /// Digits share the advance of the widest digit so numbers don't jitter when they change.
+    Tabular,
+    /// Every glyph uses the same advance (in pixels at [Atlus::PX]), e.g. for hex dumps or columns.
+    Fixed(f32),
 }
 
 impl Atlus {
@@ -259,6 +287,7 @@ impl Atlus {
 
     // should be a power of two for nice alignment
     pub const PX: usize = 64;
+    const PITCH: usize = 1024;
 
     /// Create a font atlus with ASCII characters and Wii U special chars.
     pub fn new() -> Result<Self, GuiiError> {
@@ -276,25 +305,97 @@ impl Atlus {
     ///
     /// `charset` must only contain unique characters and should contain the '�' char.
     pub fn from_charset(charset: impl AsRef<[char]>) -> Result<Self, GuiiError> {
-        let chars = charset.as_ref();
+        Self::from_sources(
+            charset,
+            &[FontSource::System(wut::font::FontType::Standard)],
+        )
+    }
+
+    /// Create a font atlus from an ordered list of font sources.
+    ///
+    /// Each char is taken from the first source that has a glyph for it (see [FontChain]).
+    pub fn from_sources(
+        charset: impl AsRef<[char]>,
+        sources: &[FontSource],
+    ) -> Result<Self, GuiiError> {
+        Self::from_fonts(charset, &FontChain::new(sources)?, None)
+    }
+
+    /// Create a font atlus with the default chars ([Atlus::new]) from TTF/OTF data, e.g. `include_bytes!("font.ttf")`.
+    ///
+    /// Chars missing in the font (like the Wii U icons) fall back to the system font.
+    pub fn from_bytes(data: &[u8], settings: FontSettings) -> Result<Self, GuiiError> {
+        Self::custom(FontSource::Data(data), settings)
+    }
 
-        let font = fontdue::Font::from_bytes(
-            wut::font::system_font(wut::font::FontType::Standard)?,
-            fontdue::FontSettings::default(),
+    /// Like [Atlus::from_bytes] but reads the font from `path`, e.g. `sd:/fonts/brand.ttf`.
+    pub fn from_path(path: &str, settings: FontSettings) -> Result<Self, GuiiError> {
+        Self::custom(FontSource::File(path), settings)
+    }
+
+    fn custom(source: FontSource, settings: FontSettings) -> Result<Self, GuiiError> {
+        let mut chain = FontChain::from(source.load_with(settings)?);
+        chain.push(FontSource::System(wut::font::FontType::Standard).load()?);
+
+        Self::from_fonts(Self::DEFAULT_CHARS, &chain, None)
+    }
+
+    /// Create a font atlus from already loaded fonts.
+    ///
+    /// Every char of `charset` is rasterized with `regular` and, if given, a second time with `emphasis`.
+    pub fn from_fonts(
+        charset: impl AsRef<[char]>,
+        regular: &FontChain,
+        emphasis: Option<&FontChain>,
+    ) -> Result<Self, GuiiError> {
+        Self::from_data(&Self::rasterize(charset, regular, emphasis))
+    }
+
+    /// Rasterizes glyphs like [Atlus::from_fonts] without uploading them, e.g. to store them with [AtlusData::encode].
+    pub fn rasterize<'f>(
+        charset: impl AsRef<[char]>,
+        regular: &'f FontChain,
+        emphasis: Option<&'f FontChain>,
+    ) -> AtlusData {
+        let regular = |c| regular.font_for(c);
+        let emphasis = emphasis.map(|chain| move |c| chain.font_for(c));
+
+        AtlusData::rasterize(
+            charset.as_ref(),
+            Self::PX,
+            Self::PITCH,
+            &regular,
+            emphasis
+                .as_ref()
+                .map(|f| f as &dyn Fn(char) -> &'f fontdue::Font),
         )
-        .map_err(|e| GuiiError::FontdueError(e))?;
+    }
 
-        let mut coords = HashMap::new();
+    /// Loads an atlus stored with [AtlusData::encode], skipping rasterization.
+    pub fn from_cache(bytes: &[u8]) -> Result<Self, GuiiError> {
+        Self::from_data(&AtlusData::decode(bytes)?)
+    }
+
+    /// Like [Atlus::from_cache] but rejects caches that were not built for `charset`.
+    pub fn from_cache_checked(bytes: &[u8], charset: impl AsRef<[char]>) -> Result<Self, GuiiError> {
+        let data = AtlusData::decode(bytes)?;
 
-        const PITCH: usize = 1024;
+        if !data.matches(charset.as_ref()) {
+            return Err(CacheError::Charset.into());
+        }
 
-        let length = chars.len();
+        Self::from_data(&data)
+    }
 
-        let columns = PITCH / Self::PX;
-        let rows = (length + columns - 1) / columns;
+    /// Uploads rasterized glyphs into a texture.
+    pub fn from_data(data: &AtlusData) -> Result<Self, GuiiError> {
+        if data.px != Self::PX {
+            return Err(CacheError::Px(data.px).into());
+        }
 
-        let height = rows * Self::PX;
-        let width = columns * Self::PX;
+        let (width, height) = (data.width, data.height);
+
+        let mut coords = HashMap::new();
 
         let mut tex = Texture::new(
             Surface::new()
@@ -319,40 +420,31 @@ impl Atlus {
         tex.as_raw_mut().surface.image = unsafe { GLOBAL_ALLOCATOR.alloc(layout) } as *mut _;
 
         assert_eq!(tex.as_raw_mut().surface.image.is_null(), false);
-        assert_eq!(tex.surface().as_raw().pitch as usize, PITCH);
+        let pitch = tex.surface().as_raw().pitch as usize;
 
         let img = tex.surface_mut().image_mut().unwrap();
 
-        for (i, c) in chars.iter().copied().enumerate() {
-            let row = i / columns;
-            let col = i % columns;
-
-            let (metrics, bitmap) = font.rasterize(c, Self::PX as f32);
-
-            let start_x = col * Self::PX;
-            let start_y = row * Self::PX;
-
-            for y in 0..metrics.height {
-                for x in 0..metrics.width {
-                    let src_idx = y * metrics.width + x;
-                    let dst_x = start_x + x;
-                    let dst_y = start_y + y;
-                    let dst_idx = dst_y * PITCH + dst_x;
-
-                    assert!(dst_idx < img.len());
+        // rows are padded to the pitch the surface was laid out with
+        for (row, line) in img.chunks_mut(pitch).zip(data.bitmap.chunks_exact(width)) {
+            row[..width].copy_from_slice(line);
+        }
 
-                    img[dst_idx] = bitmap[src_idx];
-                }
-            }
+        for glyph in &data.glyphs {
+            let metrics = glyph.metrics;
+            let face = if glyph.emphasis {
+                Face::Emphasis
+            } else {
+                Face::Regular
+            };
 
             coords.insert(
-                c,
+                (face, glyph.c),
                 (
                     TexCoords {
-                        left: start_x as f32 / width as f32,
-                        right: (start_x + metrics.width) as f32 / width as f32,
-                        top: start_y as f32 / height as f32,
-                        bottom: (start_y + metrics.height) as f32 / height as f32,
+                        left: glyph.x as f32 / width as f32,
+                        right: (glyph.x + metrics.width) as f32 / width as f32,
+                        top: glyph.y as f32 / height as f32,
+                        bottom: (glyph.y + metrics.height) as f32 / height as f32,
                     },
                     metrics,
                 ),
@@ -361,13 +453,28 @@ impl Atlus {
 
         tex.invalidate();
 
-        Ok(Self { tex, coords })
+        let digit_advance = ('0'..='9')
+            .filter_map(|c| coords.get(&(Face::Regular, c)))
+            .map(|(_, metrics)| metrics.advance_width)
+            .fold(0.0, f32::max);
+
+        Ok(Self {
+            tex,
+            coords,
+            digit_advance,
+        })
     }
 
     pub fn get(&self, character: char) -> &(TexCoords, Metrics) {
-        match self.coords.get(&character) {
+        self.get_face(Face::Regular, character)
+    }
+
+    /// Like [Atlus::get] but for a specific [Face]. Missing glyphs fall back to the regular face and then to '�'.
+    pub fn get_face(&self, face: Face, character: char) -> &(TexCoords, Metrics) {
+        match self.coords.get(&(face, character)) {
             Some(coords) => coords,
-            None => self.coords.get(&'�').unwrap(),
+            None if face != Face::Regular => self.get_face(Face::Regular, character),
+            None => self.coords.get(&(Face::Regular, '�')).unwrap(),
         }
     }
 
@@ -375,23 +482,59 @@ impl Atlus {
         &self.tex
     }
 
+    /// Advance of a glyph with `spacing` and the offset that centers it in that advance (both at [Atlus::PX]).
+    pub fn advance(&self, metrics: &Metrics, c: char, spacing: Spacing) -> (f32, f32) {
+        let advance = match spacing {
+            Spacing::Proportional => return (0.0, metrics.advance_width),
+            Spacing::Tabular if c.is_ascii_digit() => self.digit_advance,
+            Spacing::Tabular => return (0.0, metrics.advance_width),
+            Spacing::Fixed(advance) => advance,
+        };
+
+        ((advance - metrics.advance_width) / 2.0, advance)
+    }
+
+    /// Widest advance of the digits `0`-`9` at [Atlus::PX], e.g. for [Spacing::Fixed].
+    pub fn digit_advance(&self) -> f32 {
+        self.digit_advance
+    }
+
     pub fn layout(&self, text: &str, scale: impl Scaling) -> Vec2<f32> {
+        self.layout_spaced(text, scale, Spacing::Proportional)
+    }
+
+    pub fn layout_spaced(&self, text: &str, scale: impl Scaling, spacing: Spacing) -> Vec2<f32> {
+        self.layout_glyphs(text.chars().map(markup::Glyph::plain), scale, spacing)
+    }
+
+    /// Size of `text` when rendered as markup (see [markup]).
+    pub fn layout_markup(&self, text: &str, scale: impl Scaling) -> Vec2<f32> {
+        self.layout_glyphs(markup::Markup::new(text), scale, Spacing::Proportional)
+    }
+
+    pub fn layout_glyphs(
+        &self,
+        glyphs: impl Iterator<Item = markup::Glyph>,
+        scale: impl Scaling,
+        spacing: Spacing,
+    ) -> Vec2<f32> {
         let scale = scale.relative(Self::PX);
         let mut size = Vec2::new(0.0, (Self::PX as f32 * scale));
 
         let mut width = 0.0;
 
-        for c in text.chars() {
-            if c == '\n' {
+        for glyph in glyphs {
+            if glyph.c == '\n' {
                 size.x = size.x.max(width);
                 size.y += (Self::PX as f32 * scale);
                 width = 0.0;
                 continue;
             }
 
-            let (_, metrics) = self.get(c);
+            let (_, metrics) = self.get_face(glyph.face, glyph.c);
+            let (_, advance) = self.advance(metrics, glyph.c, spacing);
 
-            width += (metrics.advance_width * scale);
+            width += (advance * scale);
         }
 
         size.x = size.x.max(width);
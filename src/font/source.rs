@@ -0,0 +1,112 @@
+use crate::GuiiError;
+use alloc::string::{String, ToString};
+use fontdue::FontSettings;
+use wut::{font::FontType, format, vec::Vec};
+
+/// Where the data of a font comes from.
+#[derive(Debug, Clone, Copy)]
+pub enum FontSource<'a> {
+    /// One of the fonts shipped with the console.
+    System(FontType),
+    /// TTF/OTF data supplied by the application, e.g. via `include_bytes!`.
+    Data(&'a [u8]),
+    /// TTF/OTF file on a filesystem. `sd:/` is resolved to the SD card (`fs:/vol/external01/`).
+    File(&'a str),
+}
+
+impl FontSource<'_> {
+    pub fn load(&self) -> Result<fontdue::Font, GuiiError> {
+        self.load_with(FontSettings::default())
+    }
+
+    /// Loads the font with custom `settings` (scale, collection index).
+    pub fn load_with(&self, settings: FontSettings) -> Result<fontdue::Font, GuiiError> {
+        match self {
+            Self::System(font) => {
+                fontdue::Font::from_bytes(wut::font::system_font(*font)?, settings)
+                    .map_err(GuiiError::FontdueError)
+            }
+            Self::Data(data) => {
+                fontdue::Font::from_bytes(*data, settings).map_err(GuiiError::InvalidFont)
+            }
+            Self::File(path) => {
+                let path = resolve(path);
+                let data = wut::fs::read(&path).map_err(|source| GuiiError::FontFile {
+                    path: path.clone(),
+                    source,
+                })?;
+
+                fontdue::Font::from_bytes(data.as_slice(), settings)
+                    .map_err(|e| GuiiError::InvalidFontFile { path, reason: e })
+            }
+        }
+    }
+}
+
+pub(crate) fn resolve(path: &str) -> String {
+    match path.strip_prefix("sd:/") {
+        Some(rest) => format!("fs:/vol/external01/{}", rest),
+        None => path.to_string(),
+    }
+}
+
+/// Ordered list of fonts. Each glyph is taken from the first font that contains it.
+pub struct FontChain {
+    fonts: Vec<fontdue::Font>,
+}
+
+impl FontChain {
+    /// Loads all `sources` in order.
+    pub fn new(sources: &[FontSource]) -> Result<Self, GuiiError> {
+        Self::from_fonts(
+            sources
+                .iter()
+                .map(|s| s.load())
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    }
+
+    /// Chain of already loaded fonts. At least one font is required.
+    pub fn from_fonts(fonts: Vec<fontdue::Font>) -> Result<Self, GuiiError> {
+        if fonts.is_empty() {
+            Err(GuiiError::EmptyFontChain)
+        } else {
+            Ok(Self { fonts })
+        }
+    }
+
+    /// All system font types, starting with [FontType::Standard].
+    pub fn system() -> Result<Self, GuiiError> {
+        Self::new(&[
+            FontSource::System(FontType::Standard),
+            FontSource::System(FontType::Chinese),
+            FontSource::System(FontType::Korean),
+            FontSource::System(FontType::Taiwanese),
+        ])
+    }
+
+    /// Appends a font with the lowest priority.
+    pub fn push(&mut self, font: fontdue::Font) {
+        self.fonts.push(font);
+    }
+
+    /// First font containing `c`. If no font has it, the first font is returned so it can render its own replacement glyph.
+    pub fn font_for(&self, c: char) -> &fontdue::Font {
+        self.fonts
+            .iter()
+            .find(|font| font.lookup_glyph_index(c) != 0)
+            .unwrap_or(&self.fonts[0])
+    }
+
+    pub fn fonts(&self) -> &[fontdue::Font] {
+        &self.fonts
+    }
+}
+
+impl From<fontdue::Font> for FontChain {
+    fn from(font: fontdue::Font) -> Self {
+        Self {
+            fonts: Vec::from([font]),
+        }
+    }
+}
@@ -1,5 +1,18 @@
-use crate::{config::Config, error::GuiiError, focus::Focus, font::Atlus, ui::Ui, vector::Vector};
+use crate::{
+    animation::Animations,
+    clock::Clock,
+    config::{Config, controls::Action},
+    error::GuiiError,
+    focus::Focus,
+    font::Atlus,
+    input::{Repeater, Sources, StickNav},
+    pages::{Pages, Request},
+    touch::Touch,
+    ui::Ui,
+    vector::Vector,
+};
 use core::marker::PhantomData;
+use flagset::FlagSet;
 use wut::gx2::{
     buffer::Flags,
     shader::{
@@ -19,7 +32,13 @@ pub struct Guii<T: RenderTarget> {
     pub(crate) sampler: Sampler,
     pub(crate) atlus: Atlus,
     pub(crate) focus: Focus,
-    pub(crate) gamepad: wut::gamepad::Gamepad,
+    pub(crate) sources: Sources,
+    pub(crate) touch: Touch,
+    pub(crate) repeater: Repeater,
+    pub(crate) stick: StickNav,
+    pub(crate) clock: Clock,
+    pub(crate) pages: Pages,
+    pub(crate) animations: Animations,
     // indices: Vector<u32>,
     projection: Mat4x4<f32>,
     shader: shader::Shader,
@@ -37,7 +56,13 @@ impl<T: RenderTarget> Guii<T> {
             sampler: Sampler::new(TexClamp::Clamp, TexXyFilter::Linear),
             atlus: Atlus::new()?,
             focus: Focus::new(),
-            gamepad: wut::gamepad::Gamepad::new(wut::gamepad::Port::DRC),
+            sources: Sources::new(),
+            touch: Touch::default(),
+            repeater: Repeater::new(),
+            stick: StickNav::new(),
+            clock: Clock::new(),
+            pages: Pages::new(),
+            animations: Animations::new(),
             // indices: Vector::new(Flags::BindIndexBuffer)?,
             projection: T::ortho(),
             shader: shader::Shader::new(
@@ -57,18 +82,56 @@ impl<T: RenderTarget> Guii<T> {
         Self::new_indexed(0)
     }
 
-    pub fn build<F: FnOnce(&mut Ui<T>) -> ()>(&mut self, style: Config, f: F) {
+    /// Controllers polled for input, e.g. to disable KPAD controllers.
+    pub fn sources_mut(&mut self) -> &mut Sources {
+        &mut self.sources
+    }
+
+    /// Page stack, e.g. to [reset](Pages::reset) it to another root page.
+    pub fn pages_mut(&mut self) -> &mut Pages {
+        &mut self.pages
+    }
+
+    /// Replaces the font atlus, e.g. with one that has an emphasis face ([Atlus::from_fonts]).
+    pub fn set_atlus(&mut self, atlus: Atlus) {
+        self.atlus = atlus;
+    }
+
+    /// Builds the UI of this frame. Returns the actions no widget handled, e.g. [Action::Cancel] to leave a menu.
+    pub fn build<F: FnOnce(&mut Ui<T>) -> ()>(&mut self, style: Config, f: F) -> FlagSet<Action> {
         self.vertices.clear();
         self.tex.clear();
         self.colors.clear();
+        self.clock.tick();
 
-        let items = {
+        let mut actions = {
             let mut context = Ui::new(self, style);
             f(&mut context);
-            context.index
+            context.actions
         };
 
-        self.focus.clamp(0, items);
+        self.focus.end_frame();
+        self.animations.end_frame(self.clock.frame);
+
+        if actions.contains(Action::Home) {
+            self.focus.first();
+            actions -= Action::Home;
+        }
+        if actions.contains(Action::End) {
+            self.focus.last();
+            actions -= Action::End;
+        }
+
+        // unhandled cancel goes back one page
+        if self.pages.request.is_none() && self.pages.depth() > 1 && actions.contains(Action::Cancel) {
+            self.pages.request = Some(Request::Pop);
+            actions -= Action::Cancel;
+        }
+
+        if let Some(focus) = self.pages.apply(self.focus.get(), self.clock.time) {
+            self.focus.forget();
+            self.focus.set(focus);
+        }
 
         // self.shader
         //     .attributes
@@ -78,6 +141,8 @@ impl<T: RenderTarget> Guii<T> {
         //     .attributes
         //     .color
         //     .set_buffer(self.colors.get().try_into().unwrap());
+
+        actions
     }
 }
 
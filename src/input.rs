@@ -0,0 +1,346 @@
+//! Controller input merged from the GamePad and KPAD based controllers (Wii Remote, Classic Controller, Pro Controller).
+
+use crate::config::controls::{Action, Repeat, Stick};
+use flagset::{FlagSet, Flags, flags};
+use wut::{gamepad, gx2::types::Vec2, sys, vec::Vec};
+
+flags! {
+    /// Wii Remote buttons (`WPAD_BUTTON_*`).
+    pub enum WiiRemoteButton: u32 {
+        Left = 0x0001,
+        Right = 0x0002,
+        Down = 0x0004,
+        Up = 0x0008,
+        Plus = 0x0010,
+        Two = 0x0100,
+        One = 0x0200,
+        B = 0x0400,
+        A = 0x0800,
+        Minus = 0x1000,
+        Z = 0x2000,
+        C = 0x4000,
+        Home = 0x8000,
+    }
+
+    /// Classic and Pro Controller buttons (`WPAD_CLASSIC_BUTTON_*`, `WPAD_PRO_BUTTON_*`).
+    pub enum ClassicButton: u32 {
+        Up = 0x0001,
+        Left = 0x0002,
+        ZR = 0x0004,
+        X = 0x0008,
+        A = 0x0010,
+        Y = 0x0020,
+        B = 0x0040,
+        ZL = 0x0080,
+        R = 0x0200,
+        Plus = 0x0400,
+        Home = 0x0800,
+        Minus = 0x1000,
+        L = 0x2000,
+        Down = 0x4000,
+        Right = 0x8000,
+        /// Right stick click, Pro Controller only. The Classic Controller reports its left stick pushed up
+        /// (`WPAD_CLASSIC_STICK_L_EMULATION_UP`) with this bit, so don't bind it for `classic`.
+        StickR = 0x10000,
+        /// Left stick click, Pro Controller only. The Classic Controller reports its left stick pushed down
+        /// (`WPAD_CLASSIC_STICK_L_EMULATION_DOWN`) with this bit, so don't bind it for `classic`.
+        StickL = 0x20000,
+    }
+}
+
+/// Button state of a single frame.
+#[derive(Debug, Clone, Copy)]
+pub struct Buttons<B: Flags> {
+    pub hold: FlagSet<B>,
+    pub trigger: FlagSet<B>,
+    pub release: FlagSet<B>,
+}
+
+impl<B: Flags<Type = u32>> Buttons<B> {
+    fn from_raw(hold: u32, trigger: u32, release: u32) -> Self {
+        Self {
+            hold: FlagSet::new_truncated(hold),
+            trigger: FlagSet::new_truncated(trigger),
+            release: FlagSet::new_truncated(release),
+        }
+    }
+}
+
+/// A KPAD controller and its buttons.
+#[derive(Debug, Clone, Copy)]
+pub enum Kpad {
+    /// Wii Remote, with or without Nunchuk.
+    WiiRemote(Buttons<WiiRemoteButton>),
+    /// Classic Controller. Buttons on the Wii Remote it is plugged into are ignored.
+    Classic(Buttons<ClassicButton>),
+    Pro(Buttons<ClassicButton>),
+}
+
+/// Input of all connected controllers for one frame.
+pub struct Input {
+    pub gamepad: Option<gamepad::State>,
+    pub kpads: Vec<Kpad>,
+    /// Most deflected left stick (or Nunchuk stick) of all controllers, in `-1.0..=1.0`.
+    pub stick: Vec2<f32>,
+}
+
+/// Polls the GamePad and the KPAD channels.
+pub struct Sources {
+    gamepad: Option<gamepad::Gamepad>,
+    kpad: bool,
+}
+
+impl Sources {
+    const CHANNELS: [sys::WPADChan::Type; 4] = [
+        sys::WPADChan::WPAD_CHAN_0,
+        sys::WPADChan::WPAD_CHAN_1,
+        sys::WPADChan::WPAD_CHAN_2,
+        sys::WPADChan::WPAD_CHAN_3,
+    ];
+
+    /// GamePad and all KPAD channels.
+    pub fn new() -> Self {
+        let mut sources = Self {
+            gamepad: Some(gamepad::Gamepad::new(gamepad::Port::DRC)),
+            kpad: false,
+        };
+        sources.set_kpad(true);
+        sources
+    }
+
+    /// Enables or disables polling the GamePad.
+    pub fn set_gamepad(&mut self, enabled: bool) {
+        self.gamepad = enabled.then(|| gamepad::Gamepad::new(gamepad::Port::DRC));
+    }
+
+    /// Enables or disables polling the KPAD channels.
+    pub fn set_kpad(&mut self, enabled: bool) {
+        if enabled && !self.kpad {
+            unsafe {
+                sys::KPADInit();
+                sys::WPADEnableURCC(1);
+            }
+        }
+
+        self.kpad = enabled;
+    }
+
+    pub fn poll(&mut self) -> Input {
+        let gamepad = self.gamepad.as_mut().and_then(|gamepad| gamepad.poll().ok());
+
+        let mut input = Input {
+            stick: gamepad.as_ref().map_or(Vec2::new(0.0, 0.0), |state| {
+                Vec2::new(state.left_stick.x, state.left_stick.y)
+            }),
+            gamepad,
+            kpads: Vec::new(),
+        };
+
+        if self.kpad {
+            for (kpad, stick) in Self::CHANNELS.iter().filter_map(|chan| Self::read(*chan)) {
+                if magnitude(stick) > magnitude(input.stick) {
+                    input.stick = stick;
+                }
+
+                input.kpads.push(kpad);
+            }
+        }
+
+        input
+    }
+
+    fn read(chan: sys::WPADChan::Type) -> Option<(Kpad, Vec2<f32>)> {
+        let mut status: sys::KPADStatus = unsafe { core::mem::zeroed() };
+        let mut error = sys::KPADError::KPAD_ERROR_OK;
+
+        let read = unsafe { sys::KPADReadEx(chan, &mut status, 1, &mut error) };
+
+        if read <= 0 || error != sys::KPADError::KPAD_ERROR_OK {
+            return None;
+        }
+
+        let stick = |v: sys::KPADVec2D| Vec2::new(v.x, v.y);
+
+        Some(match status.extensionType as u32 {
+            sys::WPADExtensionType::WPAD_EXT_CLASSIC
+            | sys::WPADExtensionType::WPAD_EXT_MPLUS_CLASSIC => {
+                let classic = unsafe { status.__bindgen_anon_1.classic };
+                (
+                    Kpad::Classic(Buttons::from_raw(
+                        classic.hold,
+                        classic.trigger,
+                        classic.release,
+                    )),
+                    stick(classic.leftStick),
+                )
+            }
+            sys::WPADExtensionType::WPAD_EXT_PRO_CONTROLLER => {
+                let pro = unsafe { status.__bindgen_anon_1.pro };
+                (
+                    Kpad::Pro(Buttons::from_raw(pro.hold, pro.trigger, pro.release)),
+                    stick(pro.leftStick),
+                )
+            }
+            extension => (
+                Kpad::WiiRemote(Buttons::from_raw(
+                    status.hold,
+                    status.trigger,
+                    status.release,
+                )),
+                match extension {
+                    sys::WPADExtensionType::WPAD_EXT_NUNCHUK
+                    | sys::WPADExtensionType::WPAD_EXT_MPLUS_NUNCHUK => {
+                        stick(unsafe { status.__bindgen_anon_1.nunchuk.stick })
+                    }
+                    _ => Vec2::new(0.0, 0.0),
+                },
+            ),
+        })
+    }
+}
+
+impl Default for Sources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turns a held direction into repeated actions (see [Repeat]).
+#[derive(Debug)]
+pub(crate) struct Repeater {
+    action: Option<Action>,
+    held: f32,
+    pending: f32,
+}
+
+impl Repeater {
+    pub(crate) fn new() -> Self {
+        Self {
+            action: None,
+            held: 0.0,
+            pending: 0.0,
+        }
+    }
+
+    /// Seconds the current direction has been held.
+    pub(crate) fn held(&self) -> f32 {
+        self.held
+    }
+
+    /// Returns `held` if it should repeat this frame.
+    pub(crate) fn update(
+        &mut self,
+        held: Option<Action>,
+        delta: f32,
+        config: &Repeat,
+    ) -> Option<Action> {
+        if held != self.action {
+            *self = Self::new();
+            self.action = held;
+            return None;
+        }
+
+        let held = held?;
+
+        let before = self.held;
+        self.held += delta;
+
+        if self.held < config.delay {
+            return None;
+        }
+
+        if before < config.delay {
+            return Some(held);
+        }
+
+        self.pending += delta * config.rate(self.held);
+
+        if self.pending >= 1.0 {
+            self.pending -= 1.0;
+            Some(held)
+        } else {
+            None
+        }
+    }
+}
+
+fn magnitude(v: Vec2<f32>) -> f32 {
+    v.x.abs().max(v.y.abs())
+}
+
+/// Turns the stick into digital directions with hysteresis and into analog value steps (see [Stick]).
+#[derive(Debug)]
+pub(crate) struct StickNav {
+    direction: Option<Action>,
+    accumulator: f32,
+}
+
+impl StickNav {
+    pub(crate) fn new() -> Self {
+        Self {
+            direction: None,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Direction the stick is currently held in.
+    pub(crate) fn held(&self) -> Option<Action> {
+        self.direction
+    }
+
+    /// Returns the direction once when the stick is pushed past [Stick::press]. It triggers again only after
+    /// it went back below [Stick::release].
+    pub(crate) fn update(&mut self, stick: Vec2<f32>, config: &Stick) -> Option<Action> {
+        if !config.enabled {
+            self.direction = None;
+            return None;
+        }
+
+        if let Some(direction) = self.direction {
+            let along = match direction {
+                Action::Up => stick.y,
+                Action::Down => -stick.y,
+                Action::Left => -stick.x,
+                _ => stick.x,
+            };
+
+            if along < config.release {
+                self.direction = None;
+            }
+
+            return None;
+        }
+
+        if magnitude(stick) < config.press {
+            return None;
+        }
+
+        self.direction = Some(
+            match (stick.x.abs() > stick.y.abs(), stick.x > 0.0, stick.y > 0.0) {
+                (true, true, _) => Action::Right,
+                (true, false, _) => Action::Left,
+                (false, _, true) => Action::Up,
+                (false, _, false) => Action::Down,
+            },
+        );
+
+        self.direction
+    }
+
+    /// Whole value steps (negative = left) for this frame, proportional to the horizontal deflection past `press`.
+    pub(crate) fn analog_steps(&mut self, stick: Vec2<f32>, delta: f32, config: &Stick) -> i32 {
+        let deflection = ((stick.x.abs() - config.press) / (1.0 - config.press)).clamp(0.0, 1.0);
+
+        if !config.enabled || deflection == 0.0 {
+            self.accumulator = 0.0;
+            return 0;
+        }
+
+        self.accumulator += deflection.copysign(stick.x) * config.analog_rate * delta;
+
+        let steps = self.accumulator as i32;
+        self.accumulator -= steps as f32;
+
+        steps
+    }
+}
@@ -2,11 +2,18 @@
 
 extern crate alloc;
 
+pub mod animation;
+pub mod clock;
 pub mod config;
 pub mod error;
 pub mod focus;
 pub mod font;
 pub mod guii;
+pub mod input;
+pub mod pages;
+pub mod rect;
+pub mod shapes;
+pub mod touch;
 pub mod ui;
 pub mod vector;
 pub mod widgets;
@@ -0,0 +1,147 @@
+use crate::animation::Easing;
+use wut::vec::Vec;
+
+/// Animation played when a page is pushed or popped.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Transition {
+    #[default]
+    None,
+    /// Fades the new page in over the given seconds.
+    Fade(f32),
+    /// Slides the new page in over the given seconds, from the right on push and from the left on pop.
+    Slide(f32),
+}
+
+impl Transition {
+    fn duration(&self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Fade(duration) | Self::Slide(duration) => *duration,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Page {
+    name: &'static str,
+    /// Focus the page had when another page was pushed on top of it.
+    focus: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Request {
+    /// Page and the ID of the widget it opens with focused.
+    Push(&'static str, usize),
+    Pop,
+}
+
+/// Navigation stack of named pages (menu → submenu → detail).
+///
+/// Pages are pushed with [Ui::push_page](crate::Ui::push_page) and popped with [Ui::pop_page](crate::Ui::pop_page)
+/// or by an unhandled [Action::Cancel](crate::config::controls::Action::Cancel). Popping restores the focus the
+/// parent page had.
+#[derive(Debug)]
+pub struct Pages {
+    stack: Vec<Page>,
+    pub(crate) request: Option<Request>,
+    /// Start time and direction (`true` = pop) of the running transition.
+    pub(crate) started: Option<(f32, bool)>,
+    /// The current page was opened (pushed or reset) and not built yet.
+    opened: bool,
+}
+
+impl Pages {
+    pub const ROOT: &'static str = "root";
+
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::from([Page {
+                name: Self::ROOT,
+                focus: 0,
+            }]),
+            request: None,
+            started: None,
+            opened: true,
+        }
+    }
+
+    /// Name of the visible page.
+    pub fn current(&self) -> &'static str {
+        self.stack.last().unwrap().name
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Replaces the whole stack with `root`.
+    pub fn reset(&mut self, root: &'static str) {
+        self.stack.clear();
+        self.stack.push(Page {
+            name: root,
+            focus: 0,
+        });
+        self.request = None;
+        self.started = None;
+        self.opened = true;
+    }
+
+    /// Whether the current page is built for the first time since it was opened. Clears the flag.
+    pub(crate) fn take_opened(&mut self) -> bool {
+        core::mem::take(&mut self.opened)
+    }
+
+    /// Applies a pending request. `focus` is the current focus, the returned value the focus of the new page.
+    pub(crate) fn apply(&mut self, focus: usize, time: f32) -> Option<usize> {
+        match self.request.take()? {
+            Request::Push(name, initial) => {
+                self.stack.last_mut().unwrap().focus = focus;
+                self.stack.push(Page {
+                    name,
+                    focus: initial,
+                });
+                self.started = Some((time, false));
+                self.opened = true;
+                Some(initial)
+            }
+            Request::Pop if self.stack.len() > 1 => {
+                self.stack.pop();
+                self.started = Some((time, true));
+                Some(self.stack.last().unwrap().focus)
+            }
+            Request::Pop => None,
+        }
+    }
+
+    /// Offset factor (`-1.0..=1.0` of the screen width) and alpha of the running transition.
+    pub(crate) fn transition(&mut self, transition: Transition, time: f32) -> (f32, f32) {
+        let Some((started, pop)) = self.started else {
+            return (0.0, 1.0);
+        };
+
+        let duration = transition.duration();
+        let t = if duration > 0.0 {
+            ((time - started) / duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        if t >= 1.0 {
+            self.started = None;
+        }
+
+        let eased = Easing::EaseInOut.apply(t);
+
+        match transition {
+            Transition::None => (0.0, 1.0),
+            Transition::Fade(_) => (0.0, eased),
+            Transition::Slide(_) => ((1.0 - eased) * if pop { -1.0 } else { 1.0 }, 1.0),
+        }
+    }
+}
+
+impl Default for Pages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
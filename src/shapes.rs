@@ -0,0 +1,527 @@
+//! Shapes tessellated into the triangle stream of [Ui]: rounded rects, circles, arcs, lines, convex polygons,
+//! gradients and drop shadows.
+//!
+//! Curves use [Style::segments](crate::config::style::Style::segments) segments per full circle. Lines and shape
+//! edges are anti-aliased with a one pixel wide fringe that fades out.
+
+use crate::{Ui, config::pallet::mix, rect::Rect};
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+use libm::{ceilf, cosf, sinf, sqrtf};
+use wut::{
+    gx2::{
+        color::Color,
+        target::RenderTarget,
+        types::{Extend, Vec2},
+    },
+    vec::Vec,
+};
+
+/// Corner radii of a rounded rect, see [Ui::rounded_rect].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Corners {
+    pub bottom_left: f32,
+    pub bottom_right: f32,
+    pub top_right: f32,
+    pub top_left: f32,
+}
+
+impl Corners {
+    pub const fn same(radius: f32) -> Self {
+        Self {
+            bottom_left: radius,
+            bottom_right: radius,
+            top_right: radius,
+            top_left: radius,
+        }
+    }
+
+    /// Radii grown by `by`, e.g. for an outline around a rounded rect.
+    pub fn expand(&self, by: f32) -> Self {
+        let grow = |radius: f32| if radius > 0.0 { radius + by } else { 0.0 };
+
+        Self {
+            bottom_left: grow(self.bottom_left),
+            bottom_right: grow(self.bottom_right),
+            top_right: grow(self.top_right),
+            top_left: grow(self.top_left),
+        }
+    }
+}
+
+impl From<f32> for Corners {
+    fn from(radius: f32) -> Self {
+        Self::same(radius)
+    }
+}
+
+/// Fill of [Ui::rect_gradient] and [Ui::rounded_rect_gradient], interpolated linearly across the shape.
+#[derive(Debug, Clone, Copy)]
+pub enum Gradient {
+    Solid(Color),
+    Vertical { bottom: Color, top: Color },
+    Horizontal { left: Color, right: Color },
+}
+
+impl Gradient {
+    /// Color at `x`, `y` relative to the shape, `0.0` to `1.0` from the bottom left.
+    pub fn at(&self, x: f32, y: f32) -> Color {
+        match *self {
+            Self::Solid(color) => color,
+            Self::Vertical { bottom, top } => mix(bottom, top, y.clamp(0.0, 1.0)),
+            Self::Horizontal { left, right } => mix(left, right, x.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+impl From<Color> for Gradient {
+    fn from(color: Color) -> Self {
+        Self::Solid(color)
+    }
+}
+
+fn transparent(color: Color) -> Color {
+    let (r, g, b, _) = color.into();
+    Color::new(r, g, b, 0.0)
+}
+
+impl<Target: RenderTarget> Ui<'_, Target> {
+    /// Triangle with a color per vertex, colors are interpolated across it.
+    pub fn shaded_triangle(&mut self, vert: [Vec2<f32>; 3], colors: [Color; 3]) {
+        let tex = Vec2::new(-1.0, -1.0);
+
+        for (vertex, color) in vert.into_iter().zip(colors) {
+            self.vertex(vertex.extend(self.z), tex, color);
+        }
+    }
+
+    /// Quad with a color per vertex, `vert` goes around the quad.
+    pub fn shaded_quad(&mut self, vert: [Vec2<f32>; 4], colors: [Color; 4]) {
+        self.shaded_triangle(
+            [vert[0], vert[1], vert[2]],
+            [colors[0], colors[1], colors[2]],
+        );
+        self.shaded_triangle(
+            [vert[0], vert[2], vert[3]],
+            [colors[0], colors[2], colors[3]],
+        );
+    }
+
+    /// Convex polygon through `points`, in either winding order.
+    pub fn polygon(&mut self, points: &[Vec2<f32>], color: Color) {
+        self.polygon_with(points, |_| color);
+    }
+
+    /// [Ui::polygon] with the color of each point given by `color`, e.g. a [Gradient].
+    pub fn polygon_with(&mut self, points: &[Vec2<f32>], color: impl Fn(Vec2<f32>) -> Color) {
+        if points.len() < 3 {
+            return;
+        }
+
+        for i in 1..points.len() - 1 {
+            let triangle = [points[0], points[i], points[i + 1]];
+            self.shaded_triangle(triangle, triangle.map(&color));
+        }
+
+        // anti-aliased edge, outside of the fill so the two don't blend twice
+        let area: f32 = (0..points.len())
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        // the offsets go left of the edges, which is inside for counter-clockwise points
+        let outside = if area > 0.0 { -1.0 } else { 1.0 };
+        self.strips(points, [0.0, 0.0, 0.0, outside], true, color);
+    }
+
+    /// Line from `a` to `b`.
+    pub fn line(&mut self, a: Vec2<f32>, b: Vec2<f32>, width: f32, color: Color) {
+        self.polyline(&[a, b], width, false, color);
+    }
+
+    /// Connected lines through `points` with mitered joins. `closed` also connects the last point to the first.
+    pub fn polyline(&mut self, points: &[Vec2<f32>], width: f32, closed: bool, color: Color) {
+        self.polyline_with(points, width, closed, |_| color);
+    }
+
+    /// [Ui::polyline] with the color at each point given by `color`.
+    pub fn polyline_with(
+        &mut self,
+        points: &[Vec2<f32>],
+        width: f32,
+        closed: bool,
+        color: impl Fn(Vec2<f32>) -> Color,
+    ) {
+        // solid core and the fringe around it
+        let inner = (width / 2.0 - 0.5).max(0.0);
+        let outer = width / 2.0 + 0.5;
+
+        self.strips(points, [-outer, -inner, inner, outer], closed, color);
+    }
+
+    /// Three strips along `points` between the four `offsets` from the line (positive = left), the outer two fade
+    /// to transparent at `offsets[0]` and `offsets[3]`. Strips of zero width are skipped.
+    fn strips(
+        &mut self,
+        points: &[Vec2<f32>],
+        offsets: [f32; 4],
+        closed: bool,
+        color: impl Fn(Vec2<f32>) -> Color,
+    ) {
+        /// Longest miter relative to the line width, sharper joins are cut.
+        const MITER_LIMIT: f32 = 4.0;
+
+        let n = points.len();
+        if n < 2 {
+            return;
+        }
+
+        let normal = |from: Vec2<f32>, to: Vec2<f32>| {
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let length = sqrtf(dx * dx + dy * dy).max(f32::EPSILON);
+            (-dy / length, dx / length)
+        };
+
+        let miters: Vec<(f32, f32)> = (0..n)
+            .map(|i| {
+                let before = match i {
+                    0 if closed => Some(normal(points[n - 1], points[0])),
+                    0 => None,
+                    _ => Some(normal(points[i - 1], points[i])),
+                };
+                let after = match i + 1 {
+                    next if next < n => Some(normal(points[i], points[next])),
+                    _ if closed => Some(normal(points[n - 1], points[0])),
+                    _ => None,
+                };
+
+                match (before, after) {
+                    (Some(a), Some(b)) => {
+                        let (x, y) = (a.0 + b.0, a.1 + b.1);
+                        let length = sqrtf(x * x + y * y);
+                        if length < 1e-3 {
+                            return a;
+                        }
+
+                        // scale so the edges stay parallel to both segments
+                        let (x, y) = (x / length, y / length);
+                        let cos = (x * a.0 + y * a.1).max(1.0 / MITER_LIMIT);
+                        (x / cos, y / cos)
+                    }
+                    (Some(a), None) | (None, Some(a)) => a,
+                    (None, None) => (0.0, 0.0),
+                }
+            })
+            .collect();
+
+        // the fringe fades to transparent
+        let shade = |i: usize, strip: usize| {
+            let color = color(points[i]);
+            if strip == 0 || strip == 3 {
+                transparent(color)
+            } else {
+                color
+            }
+        };
+        let at = |i: usize, offset: f32| {
+            Vec2::new(
+                points[i].x + miters[i].0 * offset,
+                points[i].y + miters[i].1 * offset,
+            )
+        };
+
+        let segments = if closed { n } else { n - 1 };
+
+        for i in 0..segments {
+            let j = (i + 1) % n;
+
+            for strip in 0..3 {
+                let (a, b) = (strip, strip + 1);
+                // e.g. the core of hairlines
+                if offsets[a] == offsets[b] {
+                    continue;
+                }
+
+                self.shaded_quad(
+                    [
+                        at(i, offsets[a]),
+                        at(i, offsets[b]),
+                        at(j, offsets[b]),
+                        at(j, offsets[a]),
+                    ],
+                    [shade(i, a), shade(i, b), shade(j, b), shade(j, a)],
+                );
+            }
+        }
+
+        self.z += Self::Z_INCREASE;
+    }
+
+    /// Points of an arc from angle `start` to `end` (radians, counter-clockwise from the right), both included.
+    fn arc_points(
+        &self,
+        points: &mut Vec<Vec2<f32>>,
+        center: Vec2<f32>,
+        radius: f32,
+        start: f32,
+        end: f32,
+    ) {
+        let segments =
+            ceilf(self.config.style.segments as f32 * (end - start).abs() / TAU).max(1.0) as usize;
+
+        for i in 0..=segments {
+            let angle = start + (end - start) * i as f32 / segments as f32;
+            points.push(Vec2::new(
+                center.x + cosf(angle) * radius,
+                center.y + sinf(angle) * radius,
+            ));
+        }
+    }
+
+    pub fn circle(&mut self, center: Vec2<f32>, radius: f32, color: Color) {
+        self.sector(center, radius, 0.0, TAU, color);
+    }
+
+    /// Circle outline, `width` is centered on the radius.
+    pub fn circle_stroke(&mut self, center: Vec2<f32>, radius: f32, width: f32, color: Color) {
+        let mut points = Vec::new();
+        self.arc_points(&mut points, center, radius, 0.0, TAU);
+        points.pop();
+
+        self.polyline(&points, width, true, color);
+    }
+
+    /// Filled circle sector ("pie slice") from angle `start` to `end`, see [Ui::arc].
+    pub fn sector(&mut self, center: Vec2<f32>, radius: f32, start: f32, end: f32, color: Color) {
+        let mut points = Vec::from([center]);
+        self.arc_points(&mut points, center, radius, start, end);
+
+        if (end - start).abs() >= TAU {
+            // full circle, the center is not on the outline
+            points.remove(0);
+            points.pop();
+        }
+
+        self.polygon(&points, color);
+    }
+
+    /// Arc from angle `start` to `end` (radians, counter-clockwise from the right), `width` is centered on the radius.
+    pub fn arc(
+        &mut self,
+        center: Vec2<f32>,
+        radius: f32,
+        start: f32,
+        end: f32,
+        width: f32,
+        color: Color,
+    ) {
+        let mut points = Vec::new();
+        self.arc_points(&mut points, center, radius, start, end);
+
+        self.polyline(&points, width, false, color);
+    }
+
+    /// Outline points of a rounded rect, counter-clockwise from the bottom left corner.
+    fn rounded_points(&self, x: f32, y: f32, w: f32, h: f32, corners: Corners) -> Vec<Vec2<f32>> {
+        let max = w.min(h) / 2.0;
+        let mut points = Vec::new();
+
+        for (radius, cx, cy, start) in [
+            (corners.bottom_left, x, y, PI),
+            (corners.bottom_right, x + w, y, PI + FRAC_PI_2),
+            (corners.top_right, x + w, y + h, 0.0),
+            (corners.top_left, x, y + h, FRAC_PI_2),
+        ] {
+            let radius = radius.clamp(0.0, max);
+
+            if radius == 0.0 {
+                points.push(Vec2::new(cx, cy));
+                continue;
+            }
+
+            // corner center, moved inwards by the radius
+            let center = Vec2::new(
+                if cx == x { x + radius } else { x + w - radius },
+                if cy == y { y + radius } else { y + h - radius },
+            );
+            self.arc_points(&mut points, center, radius, start, start + FRAC_PI_2);
+        }
+
+        points
+    }
+
+    /// Rect with rounded corners, radii are clamped to half the shorter side.
+    pub fn rounded_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        corners: impl Into<Corners>,
+        color: Color,
+    ) -> Vec2<f32> {
+        self.rounded_rect_gradient(x, y, w, h, corners, Gradient::Solid(color))
+    }
+
+    /// [Ui::rounded_rect] filled with `gradient`.
+    pub fn rounded_rect_gradient(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        corners: impl Into<Corners>,
+        gradient: Gradient,
+    ) -> Vec2<f32> {
+        // empty rects have no extent to spread the gradient over
+        let relative = |p: f32, start: f32, length: f32| {
+            if length > 0.0 {
+                (p - start) / length
+            } else {
+                0.0
+            }
+        };
+
+        let points = self.rounded_points(x, y, w, h, corners.into());
+        self.polygon_with(&points, |p| {
+            gradient.at(relative(p.x, x, w), relative(p.y, y, h))
+        });
+
+        Vec2::new(w, h)
+    }
+
+    /// Rect filled with `gradient`, like [Ui::rect] without an anti-aliased edge.
+    pub fn rect_gradient(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        gradient: Gradient,
+    ) -> Vec2<f32> {
+        let (l, r, b, t) = (x, x + w, y, y + h);
+
+        self.shaded_quad(
+            [
+                Vec2::new(l, b),
+                Vec2::new(r, b),
+                Vec2::new(r, t),
+                Vec2::new(l, t),
+            ],
+            [
+                gradient.at(0.0, 0.0),
+                gradient.at(1.0, 0.0),
+                gradient.at(1.0, 1.0),
+                gradient.at(0.0, 1.0),
+            ],
+        );
+        self.z += Self::Z_INCREASE;
+
+        Vec2::new(w, h)
+    }
+
+    /// Soft shadow of `rect` moved by `offset`, fading out over `blur` pixels around it. Draw it before the panel or
+    /// popup that casts it.
+    pub fn drop_shadow(&mut self, rect: Rect, offset: Vec2<f32>, blur: f32, color: Color) {
+        let (l, r, b, t) = (
+            rect.x + offset.x,
+            rect.x + rect.w + offset.x,
+            rect.y + offset.y,
+            rect.y + rect.h + offset.y,
+        );
+        let clear = transparent(color);
+        let solid = [color; 4];
+
+        self.shaded_quad(
+            [
+                Vec2::new(l, b),
+                Vec2::new(r, b),
+                Vec2::new(r, t),
+                Vec2::new(l, t),
+            ],
+            solid,
+        );
+
+        if blur > 0.0 {
+            // edges fade outwards
+            let fade = [color, color, clear, clear];
+            let (outer_l, outer_r, outer_b, outer_t) = (l - blur, r + blur, b - blur, t + blur);
+            self.shaded_quad(
+                [
+                    Vec2::new(l, b),
+                    Vec2::new(r, b),
+                    Vec2::new(r, outer_b),
+                    Vec2::new(l, outer_b),
+                ],
+                fade,
+            );
+            self.shaded_quad(
+                [
+                    Vec2::new(r, b),
+                    Vec2::new(r, t),
+                    Vec2::new(outer_r, t),
+                    Vec2::new(outer_r, b),
+                ],
+                fade,
+            );
+            self.shaded_quad(
+                [
+                    Vec2::new(r, t),
+                    Vec2::new(l, t),
+                    Vec2::new(l, outer_t),
+                    Vec2::new(r, outer_t),
+                ],
+                fade,
+            );
+            self.shaded_quad(
+                [
+                    Vec2::new(l, t),
+                    Vec2::new(l, b),
+                    Vec2::new(outer_l, b),
+                    Vec2::new(outer_l, t),
+                ],
+                fade,
+            );
+
+            // corners fade round around the rect corner
+            for (cx, cy, start) in [
+                (l, b, PI),
+                (r, b, PI + FRAC_PI_2),
+                (r, t, 0.0),
+                (l, t, FRAC_PI_2),
+            ] {
+                let center = Vec2::new(cx, cy);
+                let mut arc = Vec::new();
+                self.arc_points(&mut arc, center, blur, start, start + FRAC_PI_2);
+
+                for edge in arc.windows(2) {
+                    self.shaded_triangle([center, edge[0], edge[1]], [color, clear, clear]);
+                }
+            }
+        }
+
+        self.z += Self::Z_INCREASE;
+    }
+
+    /// Outline of a rounded rect, drawn inside the rect like [Ui::border].
+    pub fn rounded_border(
+        &mut self,
+        rect: Rect,
+        corners: impl Into<Corners>,
+        width: f32,
+        color: Color,
+    ) -> Vec2<f32> {
+        let half = width / 2.0;
+        let corners = corners.into().expand(-half);
+        let points = self.rounded_points(
+            rect.x + half,
+            rect.y + half,
+            rect.w - width,
+            rect.h - width,
+            corners,
+        );
+        self.polyline(&points, width, true, color);
+
+        Vec2::new(rect.w, rect.h)
+    }
+}
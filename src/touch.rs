@@ -0,0 +1,65 @@
+use wut::gx2::types::Vec2;
+
+/// Size of the GamePad screen in calibrated touch panel coordinates.
+pub const DRC_SIZE: (f32, f32) = (854.0, 480.0);
+
+/// Touch panel state, tracked across frames so presses can be turned into clicks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Touch {
+    /// Current contact point in UI space.
+    pub position: Option<Vec2<f32>>,
+    /// Where the current (or just released) touch started.
+    pub origin: Option<Vec2<f32>>,
+    /// Last contact point of a touch that ended this frame.
+    pub released: Option<Vec2<f32>>,
+}
+
+/// Touch state of a single widget.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interaction {
+    /// A finger is on the widget.
+    pub hovered: bool,
+    /// The current touch started on the widget and is still on it.
+    pub pressed: bool,
+    /// A touch that started on the widget was released on it.
+    pub clicked: bool,
+}
+
+impl Touch {
+    /// Feeds the calibrated touch panel point of this frame (top left origin, see [DRC_SIZE]).
+    ///
+    /// `size` is the size of the UI's ortho projection which has its origin in the bottom left.
+    pub(crate) fn update(&mut self, point: Option<(f32, f32)>, size: (usize, usize)) {
+        let point = point.map(|(x, y)| {
+            Vec2::new(
+                x / DRC_SIZE.0 * size.0 as f32,
+                (DRC_SIZE.1 - y) / DRC_SIZE.1 * size.1 as f32,
+            )
+        });
+
+        self.released = match (self.position, point) {
+            (Some(last), None) => Some(last),
+            _ => None,
+        };
+
+        if self.position.is_none() {
+            self.origin = point;
+        }
+
+        self.position = point;
+    }
+
+    pub fn interact(&self, x: f32, y: f32, w: f32, h: f32) -> Interaction {
+        let inside = |p: Option<Vec2<f32>>| {
+            p.is_some_and(|p| p.x >= x && p.x <= x + w && p.y >= y && p.y <= y + h)
+        };
+
+        let started = inside(self.origin);
+
+        Interaction {
+            hovered: inside(self.position),
+            pressed: started && inside(self.position),
+            clicked: started && inside(self.released),
+        }
+    }
+}
@@ -1,45 +1,449 @@
 use crate::{
-    config::{Config, layout::Scaling},
-    font::Atlus,
+    animation::Easing,
+    config::{
+        Config,
+        controls::Action,
+        layout::Scaling,
+        pallet::{ColorCombination, Pallet, State, mix},
+        style::Style,
+    },
+    font::{
+        Atlus, Spacing,
+        markup::{Glyph, Markup},
+    },
     guii::Guii,
+    input::Input,
+    pages::Request,
+    rect::Rect,
+    shapes::Corners,
+    touch::{DRC_SIZE, Interaction},
     widgets,
 };
-use wut::gx2::{
-    color::Color,
-    target::RenderTarget,
-    types::{Extend, Mat3x2, Vec2, Vec3},
+use flagset::FlagSet;
+use wut::{
+    gx2::{
+        color::Color,
+        target::RenderTarget,
+        types::{Extend, Mat3x2, Vec2, Vec3},
+    },
+    vec::Vec,
 };
 
 pub struct Ui<'l, Target: RenderTarget> {
     pub(crate) guii: &'l mut Guii<Target>,
-    z: f32,
+    pub(crate) z: f32,
     pub(crate) index: usize,
     pub(crate) position: Vec2<f32>,
-    pub(crate) input: Option<wut::gamepad::State>,
+    pub(crate) input: Input,
+    /// Actions of this frame that no widget handled yet.
+    pub(crate) actions: FlagSet<Action>,
+    /// Steps value widgets should take per [consumed](Ui::consume) Left/Right, grows while a direction is held.
+    pub(crate) steps: u32,
+    /// Analog value steps from the stick this frame (negative = left), for the focused widget.
+    pub(crate) analog_steps: i32,
     pub(crate) config: Config,
+    /// Widgets added now can take focus and input.
+    pub(crate) enabled: bool,
+    /// First frame of the current page (see [Ui::page_opened]).
+    opened: bool,
+    /// Pallets and styles replaced by [Ui::push_style].
+    styles: Vec<(Pallet, Style)>,
+    /// Offset and alpha applied to every vertex, used by page transitions.
+    offset: Vec2<f32>,
+    alpha: f32,
 }
 
 impl<'l, Target: RenderTarget> Ui<'l, Target> {
-    const Z_INCREASE: f32 = 0.0001;
+    pub(crate) const Z_INCREASE: f32 = 0.0001;
 
     pub(crate) fn new(guii: &'l mut Guii<Target>, style: Config) -> Self {
-        let input = guii.gamepad.poll().unwrap();
+        let input = guii.sources.poll();
+
+        // the touch panel only covers the GamePad screen, a UI on the TV doesn't get touches
+        let (w, h) = DRC_SIZE;
+        let drc = Target::size() == (w as usize, h as usize);
+
+        guii.touch.update(
+            input
+                .gamepad
+                .as_ref()
+                .and_then(|state| state.touch)
+                .filter(|_| drc)
+                .map(|t| (t.x as f32, t.y as f32)),
+            Target::size(),
+        );
+
+        let controls = &style.controls;
+        let delta = guii.clock.delta;
+
+        let stick = guii.stick.update(input.stick, &controls.stick);
+        let analog_steps = guii.stick.analog_steps(input.stick, delta, &controls.stick);
+
+        // held stick directions only repeat vertically, horizontally they adjust values analog
+        let held = Action::direction(controls.held(&input)).or(
+            guii.stick
+                .held()
+                .filter(|held| matches!(held, Action::Up | Action::Down)),
+        );
+        let repeated = guii.repeater.update(held, delta, &controls.repeat);
+
+        let mut actions = controls.check(&input);
+        if let Some(stick) = stick {
+            actions |= stick;
+        }
+
+        let mut steps = 1;
+        if let Some(repeated) = repeated
+            && Action::direction(actions).is_none()
+        {
+            actions |= repeated;
+            steps = controls.repeat.steps(guii.repeater.held());
+        }
+
+        guii.focus.wrap = style.controls.wrap;
+        guii.focus.navigation = style.controls.navigation;
+
+        let (slide, alpha) = guii.pages.transition(style.transition, guii.clock.time);
+        let opened = guii.pages.take_opened();
 
         Self {
             guii,
             z: 0.0,
             index: 0,
             position: Vec2::new(80.0, 1000.0),
-            input: Some(input),
+            input,
+            actions,
+            steps,
+            analog_steps,
+            enabled: true,
+            opened,
+            styles: Vec::new(),
             config: style,
+            offset: Vec2::new(slide * Target::size().0 as f32, 0.0),
+            alpha,
         }
     }
 
+    pub fn pallet(&self) -> &Pallet {
+        &self.config.pallet
+    }
+
+    /// Pallet of the widgets added from now on, see [Ui::scope] to change it temporarily.
+    pub fn pallet_mut(&mut self) -> &mut Pallet {
+        &mut self.config.pallet
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.config.style
+    }
+
+    /// Style of the widgets added from now on, see [Ui::scope] to change it temporarily.
+    pub fn style_mut(&mut self) -> &mut Style {
+        &mut self.config.style
+    }
+
+    /// Overrides the pallet and style until the matching [Ui::pop_style].
+    ///
+    /// ```ignore
+    /// ui.push_style(|pallet, _| pallet.widget = pallet.error);
+    /// ui.button("Delete");
+    /// ui.pop_style();
+    /// ```
+    pub fn push_style(&mut self, f: impl FnOnce(&mut Pallet, &mut Style)) {
+        self.styles
+            .push((self.config.pallet.clone(), self.config.style.clone()));
+        f(&mut self.config.pallet, &mut self.config.style);
+    }
+
+    /// Restores the pallet and style of before the last [Ui::push_style].
+    pub fn pop_style(&mut self) {
+        if let Some((pallet, style)) = self.styles.pop() {
+            self.config.pallet = pallet;
+            self.config.style = style;
+        }
+    }
+
+    /// Adds widgets with a pallet and style that is restored afterwards, e.g. a smaller font in a footer.
+    ///
+    /// ```ignore
+    /// ui.scope(|ui| {
+    ///     ui.style_mut().font.body = 24;
+    ///     ui.label("v1.0");
+    /// });
+    /// ```
+    pub fn scope<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_style(|_, _| {});
+        let r = f(self);
+        self.pop_style();
+        r
+    }
+
+    /// Seconds since the first frame.
+    pub fn time(&self) -> f32 {
+        self.guii.clock.time
+    }
+
+    /// Seconds since the previous frame.
+    pub fn delta(&self) -> f32 {
+        self.guii.clock.delta
+    }
+
+    /// Moves a value towards `target` over `duration` seconds and returns its current value, e.g. for a scroll
+    /// offset. `id` identifies the value across frames (see [Ui::next_id]), the first call returns `target`. Values
+    /// that are not animated for a frame are forgotten. `usize::MAX` is reserved for the focus outline.
+    pub fn animate_value(&mut self, id: usize, target: f32, duration: f32, easing: Easing) -> f32 {
+        self.animate((id, "value"), target, duration, easing)
+    }
+
+    pub(crate) fn animate(
+        &mut self,
+        key: (usize, &'static str),
+        target: f32,
+        duration: f32,
+        easing: Easing,
+    ) -> f32 {
+        let (time, frame) = (self.guii.clock.time, self.guii.clock.frame);
+
+        self.guii
+            .animations
+            .animate(key, target, duration, easing, time, frame)
+    }
+
+    /// Actions of this frame that were not handled yet.
+    ///
+    /// Widgets handle actions while they are drawn and [consume](Ui::consume) only the ones they used. Containers
+    /// and the app can handle the rest after drawing their children, whatever is left is returned by [Guii::build].
+    pub fn actions(&self) -> FlagSet<Action> {
+        self.actions
+    }
+
+    /// Marks `action` as handled. Returns whether it was still pending.
+    pub fn consume(&mut self, action: Action) -> bool {
+        let pending = self.actions.contains(action);
+        self.actions -= action;
+        pending
+    }
+
     pub fn size(&self) -> (usize, usize) {
         Target::size()
     }
 
+    /// Steps value widgets should take this frame. Grows while a direction is held (see [Repeat::steps](crate::config::controls::Repeat::steps)).
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Value steps from analog stick deflection this frame, negative to the left (see [Stick](crate::config::controls::Stick)).
+    pub fn analog_steps(&self) -> i32 {
+        self.analog_steps
+    }
+
+    /// Whether the widget being added has focus.
+    pub fn focused(&self) -> bool {
+        self.enabled && self.guii.focus.focused(self.index)
+    }
+
+    /// ID the next focusable widget gets, e.g. for [Ui::request_focus]. IDs are the order widgets are added in, so
+    /// they stay the same between frames as long as the UI does.
+    pub fn next_id(&self) -> usize {
+        self.index
+    }
+
+    /// ID of the focused widget.
+    pub fn focused_id(&self) -> usize {
+        self.guii.focus.get()
+    }
+
+    /// Moves the focus to widget `id` from the next frame on.
+    pub fn request_focus(&mut self, id: usize) {
+        self.guii.focus.request = Some(id);
+    }
+
+    /// Whether this is the first frame of the current page.
+    pub fn page_opened(&self) -> bool {
+        self.opened
+    }
+
+    /// Finishes the focusable widget being added, the next one gets the next focus index. `rect` is used by
+    /// [Navigation::Spatial](crate::config::controls::Navigation::Spatial).
+    pub fn next_item(&mut self, rect: Rect) {
+        self.guii.focus.record(self.enabled, rect);
+        self.index += 1;
+    }
+
+    /// Moves the focus for the pending directions the focused widget did not use.
+    pub fn navigate(&mut self) {
+        for direction in Action::directions() {
+            if self.actions.contains(direction) && self.guii.focus.navigate(direction) {
+                self.actions -= direction;
+            }
+        }
+    }
+
+    /// Adds widgets that are drawn with [Pallet::disabled](crate::config::pallet::Pallet::disabled) and skipped by
+    /// focus navigation if `enabled` is false.
+    pub fn enabled<R>(&mut self, enabled: bool, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.enabled;
+        self.enabled &= enabled;
+        let r = f(self);
+        self.enabled = previous;
+        r
+    }
+
+    pub fn add_enabled<W: widgets::Widget>(&mut self, enabled: bool, widget: W) -> W::Response {
+        self.enabled(enabled, |ui| ui.add(widget))
+    }
+
+    /// Starts the [Response](widgets::Response) of the focusable widget being added, hit-testing `rect` against
+    /// the touch panel (see [Ui::interact]).
+    pub fn response(&mut self, rect: Rect) -> widgets::Response {
+        let touch = self.interact(rect.x, rect.y, rect.w, rect.h);
+
+        widgets::Response {
+            rect,
+            id: Some(self.index),
+            hovered: touch.hovered,
+            pressed: touch.pressed,
+            clicked: touch.clicked,
+            disabled: !self.enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Finishes the focusable widget of `response` after it handled its input (see [Ui::next_item]).
+    pub fn finish(&mut self, mut response: widgets::Response) -> widgets::Response {
+        response.focused = self.focused();
+        response.was_focused = self.guii.focus.report(self.index, response.focused);
+        self.next_item(response.rect);
+        response
+    }
+
+    /// Colors of the widget being added for its state (see [State]). Focus changes fade over
+    /// [Style::animation](crate::config::style::Style::animation).
+    pub fn colors(&mut self, response: &widgets::Response) -> ColorCombination {
+        let focused = self.focused() && self.config.style.focus.highlight;
+        let fade = self.animate(
+            (self.index, "focus"),
+            if focused { 1.0 } else { 0.0 },
+            self.config.style.animation,
+            Easing::EaseOut,
+        );
+
+        let pallet = &self.config.pallet;
+
+        match State::new(response.disabled, response.pressed, focused) {
+            State::Normal | State::Focused => ColorCombination::new(
+                mix(pallet.widget.base, pallet.highlight.base, fade),
+                mix(pallet.widget.content, pallet.highlight.content, fade),
+            ),
+            state => pallet.state(state),
+        }
+    }
+
+    /// Draws the background of the widget being added with the [Style](crate::config::style::Style) border and
+    /// focus outline. The outline slides to the newly focused widget.
+    pub fn background(&mut self, rect: Rect, color: Color) {
+        /// Animation ID of the focus outline, which is shared by all widgets. Reserved, no widget gets this ID.
+        const OUTLINE: usize = usize::MAX;
+
+        let style = &self.config.style;
+        let (border, focus, duration, rounding) =
+            (style.border, style.focus, style.animation, style.rounding);
+        let pallet = &self.config.pallet;
+        let (border_color, outline_color) = (pallet.border, pallet.focus_outline);
+
+        // without rounding the plain rects are cheaper and stay pixel sharp
+        if rounding > 0.0 {
+            self.rounded_rect(rect.x, rect.y, rect.w, rect.h, rounding, color);
+        } else {
+            self.rect(rect.x, rect.y, rect.w, rect.h, color);
+        }
+
+        if border > 0.0 {
+            if rounding > 0.0 {
+                self.rounded_border(rect, rounding, border, border_color);
+            } else {
+                self.border(rect.x, rect.y, rect.w, rect.h, border, border_color);
+            }
+        }
+
+        if self.focused() && focus.outline > 0.0 {
+            let offset = focus.outline + focus.outline_gap;
+            let mut slide = |channel, target| {
+                self.animate((OUTLINE, channel), target, duration, Easing::EaseOut)
+            };
+            let outline = Rect::new(
+                slide("x", rect.x - offset),
+                slide("y", rect.y - offset),
+                slide("w", rect.w + offset * 2.0),
+                slide("h", rect.h + offset * 2.0),
+            );
+
+            if rounding > 0.0 {
+                self.rounded_border(
+                    outline,
+                    Corners::same(rounding).expand(offset),
+                    focus.outline,
+                    outline_color,
+                );
+            } else {
+                self.border(
+                    outline.x,
+                    outline.y,
+                    outline.w,
+                    outline.h,
+                    focus.outline,
+                    outline_color,
+                );
+            }
+        }
+    }
+
+    /// Hit-tests a widget rect against the touch panel. Pressing a widget moves the focus to it.
+    pub fn interact(&mut self, x: f32, y: f32, w: f32, h: f32) -> Interaction {
+        if !self.enabled {
+            return Interaction::default();
+        }
+
+        let interaction = self.guii.touch.interact(x, y, w, h);
+
+        if interaction.pressed || interaction.clicked {
+            self.guii.focus.set(self.index);
+        }
+
+        interaction
+    }
+
+    /// Name of the visible page (see [Pages](crate::pages::Pages)).
+    pub fn page(&self) -> &'static str {
+        self.guii.pages.current()
+    }
+
+    /// Opens `name` on top of the current page from the next frame on, with its first widget focused.
+    pub fn push_page(&mut self, name: &'static str) {
+        self.push_page_with_focus(name, 0);
+    }
+
+    /// Like [Ui::push_page] with widget `id` of the new page focused from its first frame on, e.g. to put the
+    /// cursor on "Cancel" in a destructive confirmation.
+    pub fn push_page_with_focus(&mut self, name: &'static str, id: usize) {
+        self.guii.pages.request = Some(Request::Push(name, id));
+    }
+
+    /// Goes back to the parent page from the next frame on, restoring its focus.
+    pub fn pop_page(&mut self) {
+        self.guii.pages.request = Some(Request::Pop);
+    }
+
     pub fn vertex(&mut self, vertex: Vec3<f32>, tex: Vec2<f32>, color: Color) {
+        let vertex = Vec3::new(vertex.x + self.offset.x, vertex.y + self.offset.y, vertex.z);
+        let color = if self.alpha < 1.0 {
+            let (r, g, b, a) = color.into();
+            Color::new(r, g, b, a * self.alpha)
+        } else {
+            color
+        };
+
         let _ = self.guii.vertices.push(vertex).unwrap();
         let _ = self.guii.tex.push(tex);
         let _ = self.guii.colors.push(color).unwrap();
@@ -91,10 +495,47 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
     pub fn text(
         &mut self,
         text: &str,
+        x: f32,
+        y: f32,
+        scale: impl Scaling,
+        color: Color,
+    ) -> Vec2<f32> {
+        self.text_spaced(text, x, y, scale, color, Spacing::Proportional)
+    }
+
+    /// Like [Ui::text] with custom [Spacing], e.g. [Spacing::Tabular] for numbers that change.
+    pub fn text_spaced(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        scale: impl Scaling,
+        color: Color,
+        spacing: Spacing,
+    ) -> Vec2<f32> {
+        self.glyphs(text.chars().map(Glyph::plain), x, y, scale, color, spacing)
+    }
+
+    /// Renders `text` as markup (see [markup](crate::font::markup)). `color` is used outside of color spans.
+    pub fn markup(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        scale: impl Scaling,
+        color: Color,
+    ) -> Vec2<f32> {
+        self.glyphs(Markup::new(text), x, y, scale, color, Spacing::Proportional)
+    }
+
+    pub fn glyphs(
+        &mut self,
+        glyphs: impl Iterator<Item = Glyph>,
         mut x: f32,
         mut y: f32,
         scale: impl Scaling,
         color: Color,
+        spacing: Spacing,
     ) -> Vec2<f32> {
         let scale = scale.relative(Atlus::PX);
         // let mut x = x;
@@ -102,24 +543,26 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
 
         let origin = (x, y);
 
-        for c in text.chars() {
-            if c == '\n' {
+        for glyph in glyphs {
+            if glyph.c == '\n' {
                 x = origin.0;
                 y += (Atlus::PX as f32 * scale);
                 continue;
             }
 
-            let (tex, metrics) = self.guii.atlus.get(c);
+            let (tex, metrics) = self.guii.atlus.get_face(glyph.face, glyph.c);
 
             let tex = *tex;
             let metrics = *metrics;
+            let color = glyph.color.unwrap_or(color);
+            let (offset, advance) = self.guii.atlus.advance(&metrics, glyph.c, spacing);
 
             let w = metrics.width as f32 * scale;
             let h = metrics.height as f32 * scale;
-            let a = (metrics.advance_width * scale);
+            let a = (advance * scale);
 
             let b = y as f32 + (metrics.ymin as f32 * scale);
-            let l = x as f32 + (metrics.xmin as f32 * scale);
+            let l = x as f32 + ((offset + metrics.xmin as f32) * scale);
             let t = b + h;
             let r = l + w;
 
@@ -160,7 +603,7 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
         widget.draw(self)
     }
 
-    pub fn label(&mut self, text: &str) {
+    pub fn label(&mut self, text: &str) -> widgets::Response {
         // let size = self.text(
         //     text,
         //     self.layout.current.x,
@@ -174,7 +617,17 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
         self.add(widgets::label::Label::new(text))
     }
 
-    pub fn button(&mut self, text: &str) -> widgets::button::Response {
+    /// Label rendered as markup (see [markup](crate::font::markup)).
+    pub fn rich_label(&mut self, text: &str) -> widgets::Response {
+        self.add(widgets::label::Label::rich(text))
+    }
+
+    /// Label in the heading font size (see [Label::heading](widgets::label::Label::heading)).
+    pub fn heading(&mut self, text: &str) -> widgets::Response {
+        self.add(widgets::label::Label::heading(text))
+    }
+
+    pub fn button(&mut self, text: &str) -> widgets::Response {
         self.add(widgets::button::Button::new(text))
 
         // const PADDING: usize = 10;
@@ -216,11 +669,11 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
         value: &'a mut T,
         range: core::ops::RangeInclusive<T>,
         delta: T,
-    ) -> widgets::number::Response {
+    ) -> widgets::Response {
         self.add(widgets::number::Number::new(text, value, range, delta))
     }
 
-    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> widgets::checkbox::Response {
+    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> widgets::Response {
         /*
         const PADDING: usize = 10;
         const SCALE: usize = 32;
@@ -284,7 +737,7 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
         text: &str,
         index: &mut usize,
         options: &[T],
-    ) -> widgets::select::Response {
+    ) -> widgets::Response {
         /*
         const PADDING: usize = 10;
         const SCALE: usize = 32;
@@ -360,7 +813,7 @@ impl<'l, Target: RenderTarget> Ui<'l, Target> {
         columns: usize,
         index: &mut usize,
         data: &[T],
-    ) -> widgets::grid::Response {
+    ) -> widgets::Response {
         self.add(widgets::grid::Grid::new(text, columns, index, data))
     }
 }
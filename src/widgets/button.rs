@@ -1,10 +1,6 @@
-use crate::config::controls::Action;
+use crate::{config::controls::Action, rect::Rect};
 
-use super::Widget;
-
-pub struct Response {
-    pub clicked: bool,
-}
+use super::{Response, Widget};
 
 pub struct Button<'a> {
     text: &'a str,
@@ -20,11 +16,15 @@ impl Widget for Button<'_> {
     type Response = Response;
 
     fn draw(self, ui: &mut super::Ui<'_, impl super::RenderTarget>) -> Self::Response {
-        const PADDING: f32 = 10.0;
+        let style = &ui.config.style;
+        let (padding, spacing, px) = (style.padding, style.spacing, style.font.body);
 
-        let mut response = Response { clicked: false };
+        let size = ui.guii.atlus.layout(self.text, px);
+
+        let pos = ui.position;
+        let (w, h) = (size.x + padding.x * 2.0, size.y + padding.y);
 
-        let size = ui.guii.atlus.layout(self.text, 32);
+        let mut response = ui.response(Rect::new(pos.x, pos.y, w, h));
 
         // let color = if ui.index == ui.guii.focus {
         //     if ui.input.trigger.contains(ui.config.controls.up) {
@@ -43,42 +43,27 @@ impl Widget for Button<'_> {
         //     ui.config.pallet.widget.base
         // };
 
-        let color = if ui.guii.focus.focused(ui.index) {
-            if let Some(input) = ui.input.take() {
-                match ui.config.controls.check(&input) {
-                    Action::Up => ui.guii.focus.prev(),
-                    Action::Down => ui.guii.focus.next(),
-                    Action::Accept => response.clicked = true,
-                    _ => (),
-                }
+        if ui.focused() {
+            if ui.consume(Action::Accept) {
+                response.clicked = true;
             }
+            ui.navigate();
+        }
 
-            ui.config.pallet.highlight
-        } else {
-            ui.config.pallet.widget
-        };
+        let color = ui.colors(&response);
 
-        let pos = ui.position;
-
-        let size = ui.rect(
-            pos.x,
-            pos.y,
-            size.x + PADDING * 2.0,
-            size.y + PADDING,
-            color.base,
-        );
+        ui.background(response.rect, color.base);
 
         ui.text(
             self.text,
-            pos.x + PADDING,
-            pos.y + PADDING,
-            32,
+            pos.x + padding.x,
+            pos.y + padding.y,
+            px,
             color.content,
         );
 
-        ui.position.y -= size.y + ui.config.layout.gap.y;
-        ui.index += 1;
+        ui.position.y -= h + spacing.y;
 
-        response
+        ui.finish(response)
     }
 }
@@ -1,7 +1,3 @@
-pub struct Response {
-    pub changed: bool,
-}
-
 pub struct Checkbox<'a> {
     text: &'a str,
     value: &'a mut bool,
@@ -14,7 +10,7 @@ impl<'a> Checkbox<'a> {
 }
 
 impl super::Widget for Checkbox<'_> {
-    type Response = Response;
+    type Response = super::Response;
 
     fn draw(self, ui: &mut crate::Ui<'_, impl super::RenderTarget>) -> Self::Response {
         todo!()
@@ -1,10 +1,5 @@
 use core::fmt::Display;
 
-pub struct Response {
-    pub clicked: bool,
-    pub changed: bool,
-}
-
 pub trait Bound: Display {}
 impl<T: Display> Bound for T {}
 
@@ -27,7 +22,7 @@ impl<'a, T: Bound> Grid<'a, T> {
 }
 
 impl<T: Bound> super::Widget for Grid<'_, T> {
-    type Response = Response;
+    type Response = super::Response;
 
     fn draw(self, ui: &mut crate::Ui<'_, impl super::RenderTarget>) -> Self::Response {
         todo!()
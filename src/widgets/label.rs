@@ -1,25 +1,67 @@
 pub struct Label<'a> {
     text: &'a str,
+    rich: bool,
+    heading: bool,
 }
 
 impl<'a> Label<'a> {
     pub fn new(text: &'a str) -> Self {
-        Self { text }
+        Self {
+            text,
+            rich: false,
+            heading: false,
+        }
+    }
+
+    /// Label with inline markup (see [markup](crate::font::markup)).
+    pub fn rich(text: &'a str) -> Self {
+        Self {
+            text,
+            rich: true,
+            heading: false,
+        }
+    }
+
+    /// Label in the [heading](crate::config::style::FontSizes::heading) font size, e.g. for a page title.
+    pub fn heading(text: &'a str) -> Self {
+        Self {
+            text,
+            rich: false,
+            heading: true,
+        }
     }
 }
 
 impl super::Widget for Label<'_> {
-    type Response = ();
+    type Response = super::Response;
 
     fn draw(self, ui: &mut crate::Ui<'_, impl super::RenderTarget>) -> Self::Response {
-        let size = ui.text(
-            self.text,
+        let (x, y, color) = (
             ui.position.x,
             ui.position.y,
-            32,
-            ui.config.pallet.widget.content,
+            ui.config.pallet.background.content,
         );
+        let font = ui.config.style.font;
+        let px = if self.heading {
+            font.heading
+        } else {
+            font.body
+        };
+
+        let size = if self.rich {
+            ui.markup(self.text, x, y, px, color)
+        } else {
+            ui.text(self.text, x, y, px, color)
+        };
+
+        let response = super::Response {
+            rect: crate::rect::Rect::new(x, y, size.x, size.y),
+            disabled: !ui.enabled,
+            ..Default::default()
+        };
+
+        ui.position.y -= size.y + ui.config.style.spacing.y;
 
-        ui.position.y -= size.y + ui.config.layout.gap.y;
+        response
     }
 }
@@ -6,8 +6,11 @@ pub mod checkbox;
 pub mod grid;
 pub mod label;
 pub mod number;
+pub mod response;
 pub mod select;
 
+pub use response::Response;
+
 pub trait Widget {
     type Response;
     fn draw(self, ui: &mut super::Ui<'_, impl RenderTarget>) -> Self::Response;
@@ -2,18 +2,17 @@ use core::{
     fmt::Display,
     ops::{AddAssign, RangeInclusive, SubAssign},
 };
-use wut::{format, gx2::types::Vec2};
+use wut::format;
 
-use crate::config::controls::Action;
-
-#[derive(Debug, Default)]
-pub struct Response {
-    pub clicked: bool,
-    pub changed: bool,
-}
+use super::Response;
+use crate::{
+    config::controls::Action,
+    font::Spacing,
+    rect::Rect,
+};
 
-pub trait Bound: Sized + Display + AddAssign + SubAssign + PartialOrd {}
-impl<T: Display + AddAssign + SubAssign + PartialOrd> Bound for T {}
+pub trait Bound: Sized + Clone + Display + AddAssign + SubAssign + PartialOrd {}
+impl<T: Clone + Display + AddAssign + SubAssign + PartialOrd> Bound for T {}
 
 pub struct Number<'a, T: Bound> {
     text: &'a str,
@@ -33,14 +32,65 @@ impl<'a, T: Bound> Number<'a, T> {
     }
 }
 
+impl<T: Bound> Number<'_, T> {
+    fn decrement(&mut self) -> bool {
+        if *self.value > *self.range.start() {
+            *self.value -= self.delta.clone();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn increment(&mut self) -> bool {
+        if *self.value < *self.range.end() {
+            *self.value += self.delta.clone();
+            true
+        } else {
+            false
+        }
+    }
+}
+
 impl<T: Bound> super::Widget for Number<'_, T> {
     type Response = Response;
 
-    fn draw(self, ui: &mut super::Ui<'_, impl super::RenderTarget>) -> Self::Response {
-        const PADDING: f32 = 10.0;
-        const SCALE: usize = 32;
+    fn draw(mut self, ui: &mut super::Ui<'_, impl super::RenderTarget>) -> Self::Response {
+        let style = &ui.config.style;
+        let (padding, spacing, px) = (style.padding, style.spacing, style.font.body);
+        let (width, precision) = (style.number_width, style.number_precision);
+        let pos = ui.position;
 
-        let mut response = Response::default();
+        let format = |value: &T| {
+            format!(
+                "{}  {:0width$.precision$}  {}",
+                wut::font::icons::gamepad::LEFT,
+                value,
+                wut::font::icons::gamepad::RIGHT,
+            )
+        };
+        let value = format(self.value);
+
+        // value box, touching its left/right third steps the value
+        let x = pos.x + ui.guii.atlus.layout(self.text, px).x + padding.x;
+        let size = ui.guii.atlus.layout_spaced(&value, px, Spacing::Tabular);
+        let (w, h) = (size.x + padding.x * 2.0, size.y + padding.y);
+
+        let mut response = ui.response(Rect::new(x, pos.y, w, h));
+
+        if response.clicked {
+            match ui.guii.touch.released {
+                Some(p) if p.x < x + w / 3.0 => {
+                    response.clicked = false;
+                    response.changed |= self.decrement();
+                }
+                Some(p) if p.x > x + w * 2.0 / 3.0 => {
+                    response.clicked = false;
+                    response.changed |= self.increment();
+                }
+                _ => {}
+            }
+        }
 
         // let color = if ui.index == ui.guii.focus {
         //     if ui.input.trigger.contains(ui.config.controls.up) {
@@ -55,85 +105,68 @@ impl<T: Bound> super::Widget for Number<'_, T> {
         //     } else if ui.input.trigger.contains(ui.config.controls.left)
         //         && *self.value > *self.range.start()
         //     {
-        //         *self.value -= self.delta;
+        //         *self.value -= self.delta.clone();
         //         response.changed = true;
         //         ui.input.trigger ^= ui.config.controls.left;
         //     } else if ui.input.trigger.contains(ui.config.controls.right)
         //         && *self.value < *self.range.end()
         //     {
-        //         *self.value += self.delta;
+        //         *self.value += self.delta.clone();
         //         response.changed = true;
         //         ui.input.trigger ^= ui.config.controls.right;
         //     }
         // }
 
-        let color = if ui.guii.focus.focused(ui.index) {
-            if let Some(input) = ui.input.take() {
-                match ui.config.controls.check(&input) {
-                    Action::Up => ui.guii.focus.prev(),
-                    Action::Down => ui.guii.focus.next(),
-                    Action::Left => {
-                        if *self.value > *self.range.start() {
-                            *self.value -= self.delta;
-                            response.changed = true;
-                        }
-                    }
-                    Action::Right => {
-                        if *self.value < *self.range.end() {
-                            *self.value += self.delta;
-                            response.changed = true;
-                        }
-                    }
-                    Action::Accept => {
-                        response.clicked = true;
-                    }
-                    _ => (),
+        if ui.focused() {
+            // the stick's press also fires Left/Right, only step analog when no digital step was taken
+            let mut stepped = false;
+
+            if ui.consume(Action::Left) {
+                stepped = true;
+                for _ in 0..ui.steps {
+                    response.changed |= self.decrement();
                 }
             }
+            if ui.consume(Action::Right) {
+                stepped = true;
+                for _ in 0..ui.steps {
+                    response.changed |= self.increment();
+                }
+            }
+            if ui.consume(Action::Accept) {
+                response.clicked = true;
+            }
+            ui.navigate();
+
+            let analog_steps = if stepped { 0 } else { ui.analog_steps };
+            for _ in 0..analog_steps.unsigned_abs() {
+                response.changed |= if analog_steps < 0 {
+                    self.decrement()
+                } else {
+                    self.increment()
+                };
+            }
+        }
 
-            ui.config.pallet.highlight
-        } else {
-            ui.config.pallet.widget
-        };
-
-        let offset = ui.text(
-            self.text,
-            ui.position.x,
-            ui.position.y,
-            SCALE,
-            ui.config.pallet.widget.content,
-        ) + Vec2::new(PADDING, 0.0);
-
-        let text = format!("{:05.2}", self.value);
-
-        let size = ui.guii.atlus.layout(&text, 32);
-
-        let value_pad = PADDING + SCALE as f32 + PADDING;
+        let color = ui.colors(&response);
 
-        let size = ui.rect(
-            offset.x + ui.position.x,
-            ui.position.y,
-            value_pad + size.x + value_pad + PADDING,
-            PADDING + size.y,
-            color.base,
-        );
+        let label = ui.config.pallet.background.content;
+        ui.text(self.text, pos.x, pos.y, px, label);
 
-        ui.text(
-            &format!(
-                "{}  {}  {}",
-                wut::font::icons::gamepad::LEFT,
-                &text,
-                wut::font::icons::gamepad::RIGHT
-            ),
-            offset.x + ui.position.x + PADDING,
-            ui.position.y + PADDING,
-            32,
+        ui.background(Rect::new(x, pos.y, w, h), color.base);
+        ui.text_spaced(
+            &format(self.value),
+            x + padding.x,
+            pos.y + padding.y,
+            px,
             color.content,
+            Spacing::Tabular,
         );
 
-        ui.position.y -= size.y + ui.config.layout.gap.y;
-        ui.index += 1;
+        // label and value box
+        response.rect = Rect::new(pos.x, pos.y, x + w - pos.x, h);
+        ui.position.y -= h + spacing.y;
 
-        response
+        ui.finish(response)
     }
 }
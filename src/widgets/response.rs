@@ -0,0 +1,81 @@
+use crate::{Ui, config::controls::Action, rect::Rect};
+use wut::gx2::{target::RenderTarget, types::Vec2};
+
+/// What happened to a widget this frame, returned by all widgets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Response {
+    /// Where the widget was drawn.
+    pub rect: Rect,
+    /// Focus ID (see [Ui::next_id]), `None` for widgets that can't take the focus, e.g. labels.
+    pub id: Option<usize>,
+    /// Has the focus after handling this frame's input.
+    pub focused: bool,
+    /// Touched (see [Interaction](crate::touch::Interaction)).
+    pub hovered: bool,
+    /// Touched and held since the touch started on it.
+    pub pressed: bool,
+    /// Activated by touch or [Action::Accept].
+    pub clicked: bool,
+    /// The value of the widget changed.
+    pub changed: bool,
+    /// Added while disabled (see [Ui::enabled]).
+    pub disabled: bool,
+    pub(crate) was_focused: bool,
+}
+
+impl Response {
+    pub fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    /// Whether the widget got the focus since it was last drawn, also if that happened after it was drawn in the
+    /// previous frame (e.g. by [Ui::request_focus]).
+    pub fn gained_focus(&self) -> bool {
+        self.focused && !self.was_focused
+    }
+
+    /// Whether the widget lost the focus since it was last drawn.
+    pub fn lost_focus(&self) -> bool {
+        !self.focused && self.was_focused
+    }
+
+    /// Shows `text` above the widget while it is touched, or while it is focused and [Action::Help] is held.
+    pub fn on_hover_text(self, ui: &mut Ui<'_, impl RenderTarget>, text: &str) -> Self {
+        let help = ui.config.controls.holding(&ui.input).contains(Action::Help);
+
+        if self.hovered || (self.focused && help) {
+            let style = &ui.config.style;
+            let (padding, px, blur) = (style.padding, style.font.small, style.shadow);
+
+            let size = ui.guii.atlus.layout(text, px);
+            let (x, y) = (self.rect.x, self.rect.y + self.rect.h + style.spacing.y);
+            let (w, h) = (size.x + padding.x * 2.0, size.y + padding.y);
+            let (color, shadow) = (ui.config.pallet.highlight, ui.config.pallet.shadow);
+
+            if blur > 0.0 {
+                let rect = Rect::new(x, y, w, h);
+                ui.drop_shadow(rect, Vec2::new(0.0, -blur / 2.0), blur, shadow);
+            }
+            ui.rect(x, y, w, h, color.base);
+            ui.text(text, x + padding.x, y + padding.y, px, color.content);
+        }
+
+        self
+    }
+
+    /// Combines the responses of widgets that act as one, e.g. a label and its value. The rect covers both, the
+    /// ID is the one of `self` if it has one.
+    pub fn union(self, other: Response) -> Self {
+        Self {
+            rect: self.rect.union(other.rect),
+            id: self.id.or(other.id),
+            focused: self.focused || other.focused,
+            hovered: self.hovered || other.hovered,
+            pressed: self.pressed || other.pressed,
+            clicked: self.clicked || other.clicked,
+            changed: self.changed || other.changed,
+            disabled: self.disabled && other.disabled,
+            was_focused: self.was_focused || other.was_focused,
+        }
+    }
+}
@@ -1,9 +1,5 @@
 use core::fmt::Display;
 
-pub struct Response {
-    pub changed: bool,
-}
-
 pub trait Bound: Display {}
 impl<T: Display> Bound for T {}
 
@@ -24,7 +20,7 @@ impl<'a, T: Bound> Select<'a, T> {
 }
 
 impl<T: Bound> super::Widget for Select<'_, T> {
-    type Response = Response;
+    type Response = super::Response;
 
     fn draw(self, ui: &mut crate::Ui<'_, impl super::RenderTarget>) -> Self::Response {
         todo!()
@@ -0,0 +1,136 @@
+//! Rasterizes a font atlus on the host and writes it in the format of `guii::font::cache`.
+//!
+//! ```sh
+//! atlus-gen -o atlus.bin --charset chars.txt [--emphasis bold.ttf]... regular.ttf [fallback.ttf]...
+//! ```
+//!
+//! `chars.txt` is read as UTF-8, line breaks are ignored and every char is kept once in order of appearance.
+//! Load the result with `Atlus::from_cache(include_bytes!("atlus.bin"))`.
+
+extern crate alloc;
+
+#[allow(dead_code)]
+#[path = "../../../src/font/cache.rs"]
+mod cache;
+
+use std::{env, fs, process::ExitCode};
+
+/// Must match `guii::font::Atlus::PX`.
+const PX: usize = 64;
+/// Must match the texture pitch of `guii::font::Atlus`.
+const PITCH: usize = 1024;
+
+struct Args {
+    output: String,
+    charset: String,
+    regular: Vec<String>,
+    emphasis: Vec<String>,
+}
+
+fn parse() -> Result<Args, String> {
+    let mut output = None;
+    let mut charset = None;
+    let mut regular = Vec::new();
+    let mut emphasis = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for `{arg}`"));
+
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value()?),
+            "--charset" => charset = Some(value()?),
+            "--emphasis" => emphasis.push(value()?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => regular.push(arg),
+        }
+    }
+
+    if regular.is_empty() {
+        return Err("at least one font is required".into());
+    }
+
+    Ok(Args {
+        output: output.ok_or("missing `--output`")?,
+        charset: charset.ok_or("missing `--charset`")?,
+        regular,
+        emphasis,
+    })
+}
+
+fn load(paths: &[String]) -> Result<Vec<fontdue::Font>, String> {
+    paths
+        .iter()
+        .map(|path| {
+            let data = fs::read(path).map_err(|e| format!("reading `{path}`: {e}"))?;
+            fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+                .map_err(|e| format!("parsing `{path}`: {e}"))
+        })
+        .collect()
+}
+
+fn pick(fonts: &[fontdue::Font], c: char) -> &fontdue::Font {
+    fonts
+        .iter()
+        .find(|font| font.lookup_glyph_index(c) != 0)
+        .unwrap_or(&fonts[0])
+}
+
+fn run() -> Result<(), String> {
+    let args = parse()?;
+
+    let text = fs::read_to_string(&args.charset)
+        .map_err(|e| format!("reading `{}`: {e}", args.charset))?;
+
+    let mut chars = Vec::new();
+    for c in text.chars().filter(|c| !matches!(c, '\n' | '\r')) {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+
+    let regular = load(&args.regular)?;
+    let emphasis = load(&args.emphasis)?;
+
+    let regular = |c| pick(&regular, c);
+    let emphasis = |c| pick(&emphasis, c);
+
+    let data = cache::AtlusData::rasterize(
+        &chars,
+        PX,
+        PITCH,
+        &regular,
+        if args.emphasis.is_empty() {
+            None
+        } else {
+            Some(&emphasis)
+        },
+    );
+
+    let bytes = data.encode();
+
+    cache::AtlusData::decode(&bytes).map_err(|e| format!("verifying output: {e}"))?;
+
+    fs::write(&args.output, bytes).map_err(|e| format!("writing `{}`: {e}", args.output))?;
+
+    println!(
+        "{}: {} glyphs, {}x{} px",
+        args.output,
+        data.glyphs.len(),
+        data.width,
+        data.height
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("atlus-gen: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}